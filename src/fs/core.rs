@@ -63,13 +63,13 @@ pub struct Node {
 }
 
 #[derive(Debug)]
-pub struct Qfs<'a> {
+pub struct Qfs {
     pub inodes: InodeAllocator,
     pub inode_map: HashMap<Inode, NodeId>,
-    pub client: &'a Client,
+    pub client: Client,
     pub arena: Arena<Node>,
     pub root: NodeId,
-    // torrents: Vec<Torrent<'a>>,
+    // torrents: Vec<Torrent>,
 }
 
 fn filetype(node: &Node) -> fuser::FileType {
@@ -113,8 +113,8 @@ fn default_file_attr(node: &Node) -> FileAttr {
     }
 }
 
-impl<'a> Qfs<'a> {
-    pub fn new(client: &'a Client) -> Result<Self> {
+impl Qfs {
+    pub fn new(client: &Client) -> Result<Self> {
         let mut arena = Arena::new();
 
         let root = arena.new_node(Node {
@@ -131,7 +131,7 @@ impl<'a> Qfs<'a> {
                 next: ROOT_INODE_NUMBER + 1,
             },
             inode_map: inode_map,
-            client: client,
+            client: client.clone(),
             arena: arena,
             root: root,
         };
@@ -273,7 +273,7 @@ impl<'a> Qfs<'a> {
     }
 }
 
-impl<'a> Filesystem for Qfs<'a> {
+impl Filesystem for Qfs {
     fn lookup(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
         info!("LOOKUP: {parent:?}, {name:?}");
         let ttl = Duration::new(1, 0);
@@ -13,6 +13,7 @@ use fuser::{
 use libc::ENOENT;
 use log::LevelFilter;
 use std::ffi::OsStr;
+use std::sync::Arc;
 use std::time::{Duration, UNIX_EPOCH};
 
 fn qbt_test() {
@@ -52,7 +53,7 @@ fn qbt_test() {
     let mut torrents: Vec<qbt::torrents::Torrent> = vec![];
     qbt.get_torrent_list(&mut torrents).unwrap();
     println!("Torrent List!");
-    for i in torrents {
+    for i in torrents.into_iter().map(Arc::new) {
         println!("============================");
         println!("{:?}", i);
         println!("{:?}", i.get_generic_properties().unwrap());
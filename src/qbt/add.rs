@@ -0,0 +1,348 @@
+use reqwest::blocking::multipart::{Form, Part};
+use reqwest::blocking::RequestBuilder;
+use strum_macros::AsRefStr;
+
+use crate::qbt::core::Client;
+use crate::qbt::endpoint::{Endpoint, STOP_START_API_VERSION};
+use crate::qbt::error::{Error, Result};
+use crate::qbt::transfer::ByteRate;
+
+/// How the content of a torrent is laid out below the save path.
+#[derive(Debug, Clone, Copy, AsRefStr)]
+pub enum ContentLayout {
+    /// Keep the layout of the torrent
+    Original,
+    /// Always create a root folder
+    Subfolder,
+    /// Strip the root folder, if any
+    NoSubfolder,
+}
+
+/// Builder for `torrents/add`. At least one URL or torrent file is required.
+#[derive(Debug, Clone, Default)]
+pub struct AddTorrentRequest {
+    urls: Vec<String>,
+    torrents: Vec<(String, Vec<u8>)>,
+    savepath: Option<String>,
+    download_path: Option<String>,
+    cookie: Option<String>,
+    category: Option<String>,
+    tags: Vec<String>,
+    skip_checking: Option<bool>,
+    stopped: Option<bool>,
+    root_folder: Option<bool>,
+    content_layout: Option<ContentLayout>,
+    rename: Option<String>,
+    up_limit: Option<ByteRate>,
+    dl_limit: Option<ByteRate>,
+    ratio_limit: Option<f32>,
+    seeding_time_limit: Option<i64>,
+    auto_tmm: Option<bool>,
+    sequential_download: Option<bool>,
+    first_last_piece_prio: Option<bool>,
+}
+
+impl AddTorrentRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a magnet link or an HTTP(S) URL to a .torrent file.
+    pub fn url(mut self, url: &str) -> Self {
+        self.urls.push(url.to_string());
+        self
+    }
+
+    /// Add the raw bytes of a .torrent file, uploaded as `filename`.
+    pub fn torrent_file(mut self, filename: &str, contents: Vec<u8>) -> Self {
+        self.torrents.push((filename.to_string(), contents));
+        self
+    }
+
+    /// Download folder
+    pub fn savepath(mut self, path: &str) -> Self {
+        self.savepath = Some(path.to_string());
+        self
+    }
+
+    /// Incomplete download folder
+    pub fn download_path(mut self, path: &str) -> Self {
+        self.download_path = Some(path.to_string());
+        self
+    }
+
+    /// Cookie sent to download the .torrent file
+    pub fn cookie(mut self, cookie: &str) -> Self {
+        self.cookie = Some(cookie.to_string());
+        self
+    }
+
+    pub fn category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    /// Skip hash checking
+    pub fn skip_checking(mut self, value: bool) -> Self {
+        self.skip_checking = Some(value);
+        self
+    }
+
+    /// Add torrents in the stopped (paused) state
+    pub fn stopped(mut self, value: bool) -> Self {
+        self.stopped = Some(value);
+        self
+    }
+
+    /// Create the root folder. Superseded by `content_layout` on newer
+    /// servers.
+    pub fn root_folder(mut self, value: bool) -> Self {
+        self.root_folder = Some(value);
+        self
+    }
+
+    pub fn content_layout(mut self, layout: ContentLayout) -> Self {
+        self.content_layout = Some(layout);
+        self
+    }
+
+    /// Rename the torrent
+    pub fn rename(mut self, name: &str) -> Self {
+        self.rename = Some(name.to_string());
+        self
+    }
+
+    /// Upload speed limit
+    pub fn up_limit(mut self, limit: ByteRate) -> Self {
+        self.up_limit = Some(limit);
+        self
+    }
+
+    /// Download speed limit
+    pub fn dl_limit(mut self, limit: ByteRate) -> Self {
+        self.dl_limit = Some(limit);
+        self
+    }
+
+    /// Share ratio limit
+    pub fn ratio_limit(mut self, limit: f32) -> Self {
+        self.ratio_limit = Some(limit);
+        self
+    }
+
+    /// Seeding time limit (minutes)
+    pub fn seeding_time_limit(mut self, limit: i64) -> Self {
+        self.seeding_time_limit = Some(limit);
+        self
+    }
+
+    /// Whether Automatic Torrent Management should be used
+    pub fn auto_tmm(mut self, value: bool) -> Self {
+        self.auto_tmm = Some(value);
+        self
+    }
+
+    /// Enable sequential download
+    pub fn sequential_download(mut self, value: bool) -> Self {
+        self.sequential_download = Some(value);
+        self
+    }
+
+    /// Prioritize download of the first and last pieces
+    pub fn first_last_piece_prio(mut self, value: bool) -> Self {
+        self.first_last_piece_prio = Some(value);
+        self
+    }
+
+    /// Text fields of the multipart form. `stopped_field` is the name the
+    /// server expects for the stopped flag.
+    pub(super) fn fields(
+        &self,
+        stopped_field: &'static str,
+    ) -> Result<Vec<(&'static str, String)>> {
+        if self.urls.is_empty() && self.torrents.is_empty() {
+            let message = "No URL or torrent file to add".to_string();
+            return Err(Error::InvalidRequest(message));
+        }
+
+        let mut fields: Vec<(&'static str, String)> = vec![];
+        if !self.urls.is_empty() {
+            fields.push(("urls", self.urls.join("\n")));
+        }
+        if let Some(x) = &self.savepath {
+            fields.push(("savepath", x.clone()));
+        }
+        if let Some(x) = &self.download_path {
+            fields.push(("downloadPath", x.clone()));
+            fields.push(("useDownloadPath", true.to_string()));
+        }
+        if let Some(x) = &self.cookie {
+            fields.push(("cookie", x.clone()));
+        }
+        if let Some(x) = &self.category {
+            fields.push(("category", x.clone()));
+        }
+        if !self.tags.is_empty() {
+            fields.push(("tags", self.tags.join(",")));
+        }
+        if let Some(x) = self.skip_checking {
+            fields.push(("skip_checking", x.to_string()));
+        }
+        if let Some(x) = self.stopped {
+            fields.push((stopped_field, x.to_string()));
+        }
+        if let Some(x) = self.root_folder {
+            fields.push(("root_folder", x.to_string()));
+        }
+        if let Some(x) = self.content_layout {
+            fields.push(("contentLayout", x.as_ref().to_string()));
+        }
+        if let Some(x) = &self.rename {
+            fields.push(("rename", x.clone()));
+        }
+        if let Some(x) = self.up_limit {
+            fields.push(("upLimit", x.to_string()));
+        }
+        if let Some(x) = self.dl_limit {
+            fields.push(("dlLimit", x.to_string()));
+        }
+        if let Some(x) = self.ratio_limit {
+            fields.push(("ratioLimit", x.to_string()));
+        }
+        if let Some(x) = self.seeding_time_limit {
+            fields.push(("seedingTimeLimit", x.to_string()));
+        }
+        if let Some(x) = self.auto_tmm {
+            fields.push(("autoTMM", x.to_string()));
+        }
+        if let Some(x) = self.sequential_download {
+            fields.push(("sequentialDownload", x.to_string()));
+        }
+        if let Some(x) = self.first_last_piece_prio {
+            fields.push(("firstLastPiecePrio", x.to_string()));
+        }
+
+        Ok(fields)
+    }
+
+    /// (file name, contents) of the .torrent files to upload.
+    pub(super) fn torrent_files(&self) -> &[(String, Vec<u8>)] {
+        &self.torrents
+    }
+}
+
+/// Name of the stopped flag of `torrents/add` for a given Web API version.
+pub(super) fn stopped_field(api_version_at_least_5: bool) -> &'static str {
+    if api_version_at_least_5 {
+        "stopped"
+    } else {
+        "paused"
+    }
+}
+
+/// Check the body of a successful `torrents/add` response.
+pub(super) fn check_add_response(text: &str) -> Result<()> {
+    if text == "Fails." {
+        return Err(Error::Failed("Failed to add torrents".to_string()));
+    }
+    Ok(())
+}
+
+impl Client {
+    pub fn add_torrents(&self, request: &AddTorrentRequest) -> Result<()> {
+        let stopped = stopped_field(self.api_version_at_least(STOP_START_API_VERSION)?);
+
+        let fields = request.fields(stopped)?;
+        let endpoint = Endpoint::add_torrents();
+
+        // Multipart bodies can't be cloned, so the form is rebuilt if the
+        // request has to be replayed after logging in again.
+        let build = || -> Result<RequestBuilder> {
+            let mut form = Form::new();
+            for (key, value) in fields.iter() {
+                form = form.text(*key, value.clone());
+            }
+            for (filename, contents) in request.torrent_files() {
+                let part = Part::bytes(contents.clone())
+                    .file_name(filename.clone())
+                    .mime_str("application/x-bittorrent")?;
+                form = form.part("torrents", part);
+            }
+            Ok(self
+                .inner
+                .session
+                .post(self.url(endpoint.path))
+                .multipart(form))
+        };
+
+        let resp = self.execute(&endpoint, build)?;
+        check_add_response(&resp.text()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn form_fields() {
+        let request = AddTorrentRequest::new()
+            .url("magnet:?xt=urn:btih:a")
+            .url("https://example.com/b.torrent")
+            .download_path("/incomplete")
+            .tag("linux")
+            .tag("iso")
+            .stopped(true)
+            .content_layout(ContentLayout::NoSubfolder)
+            .dl_limit(ByteRate::kib(1));
+        let fields = request.fields(stopped_field(true)).unwrap();
+        assert_eq!(
+            fields,
+            [
+                (
+                    "urls",
+                    "magnet:?xt=urn:btih:a\nhttps://example.com/b.torrent".to_string()
+                ),
+                ("downloadPath", "/incomplete".to_string()),
+                ("useDownloadPath", "true".to_string()),
+                ("tags", "linux,iso".to_string()),
+                ("stopped", "true".to_string()),
+                ("contentLayout", "NoSubfolder".to_string()),
+                ("dlLimit", "1024".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn stopped_flag_by_version() {
+        let request = AddTorrentRequest::new()
+            .torrent_file("a.torrent", vec![b'd', b'e'])
+            .stopped(false);
+        let fields = request.fields(stopped_field(false)).unwrap();
+        assert_eq!(fields, [("paused", "false".to_string())]);
+        assert_eq!(request.torrent_files()[0].0, "a.torrent");
+    }
+
+    #[test]
+    fn nothing_to_add() {
+        let request = AddTorrentRequest::new().category("iso");
+        assert!(matches!(
+            request.fields(stopped_field(true)),
+            Err(Error::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn add_response() {
+        assert!(check_add_response("Ok.").is_ok());
+        assert!(matches!(
+            check_add_response("Fails."),
+            Err(Error::Failed(_))
+        ));
+    }
+}
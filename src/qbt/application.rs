@@ -1,7 +1,8 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use crate::qbt::core::Client;
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct BuildInfo {
@@ -12,34 +13,127 @@ pub struct BuildInfo {
     bitness: u32,
 }
 
+/// Cookie used by the server when downloading torrents and RSS feeds.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Cookie {
+    /// Cookie name
+    pub name: String,
+    /// Domain the cookie is sent to
+    pub domain: String,
+    /// Path the cookie is sent to
+    pub path: String,
+    /// Cookie value
+    pub value: String,
+    /// Expiration date (Unix Epoch)
+    #[serde(rename = "expirationDate")]
+    pub expiration_date: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkInterface {
+    /// Human readable name of the interface
+    pub name: String,
+    /// Interface id, as expected by `current_network_interface`
+    pub value: String,
+}
+
 impl Client {
     pub fn get_version(&self) -> Result<String> {
-        let endpoint = self.url("app/version");
-        let resp = self.session.get(endpoint).send()?;
+        let resp = self.send(&Endpoint::version())?;
         Ok(resp.text()?)
     }
 
     pub fn get_api_version(&self) -> Result<String> {
-        let endpoint = self.url("app/webapiVersion");
-        let resp = self.session.get(endpoint).send()?;
+        let resp = self.send(&Endpoint::webapi_version())?;
         Ok(resp.text()?)
     }
 
+    /// Check the Web API version against `(major, minor, patch)`, fetching it
+    /// once and caching it for the lifetime of the client.
+    pub fn api_version_at_least(&self, version: (u32, u32, u32)) -> Result<bool> {
+        let current = match self.inner.api_version.get() {
+            Some(x) => x,
+            None => self.inner.api_version.set(&self.get_api_version()?)?,
+        };
+        Ok(current >= version)
+    }
+
     pub fn get_build_info(&self) -> Result<BuildInfo> {
-        let endpoint = self.url("app/buildInfo");
-        let resp = self.session.get(endpoint).send()?;
-        let result: BuildInfo = resp.json()?;
+        let resp = self.send(&Endpoint::build_info())?;
+        let result: BuildInfo = parse_json(resp)?;
+        Ok(result)
+    }
+
+    pub fn shutdown(&self) -> Result<()> {
+        self.send(&Endpoint::shutdown())?;
+        Ok(())
+    }
+
+    pub fn get_cookies(&self) -> Result<Vec<Cookie>> {
+        let resp = self.send(&Endpoint::cookies())?;
+        let result: Vec<Cookie> = parse_json(resp)?;
         Ok(result)
     }
 
-    // TODO:
-    //   * shutdown
-    //   * preferences
-    //   * setPreferences
+    /// Replace every cookie of the server with `cookies`.
+    pub fn set_cookies(&self, cookies: &[Cookie]) -> Result<()> {
+        self.send(&Endpoint::set_cookies(cookies)?)?;
+        Ok(())
+    }
+
+    pub fn get_network_interfaces(&self) -> Result<Vec<NetworkInterface>> {
+        let resp = self.send(&Endpoint::network_interfaces())?;
+        let result: Vec<NetworkInterface> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Addresses of `iface` (a `NetworkInterface::value`), or of every
+    /// interface if `iface` is empty.
+    pub fn get_network_interface_addresses(&self, iface: &str) -> Result<Vec<String>> {
+        let resp = self.send(&Endpoint::network_interface_addresses(iface))?;
+        let result: Vec<String> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Send a test email using the mail notification preferences.
+    pub fn send_test_email(&self) -> Result<()> {
+        self.send(&Endpoint::send_test_email())?;
+        Ok(())
+    }
 
     pub fn get_default_save_path(&self) -> Result<String> {
-        let endpoint = self.url("app/defaultSavePath");
-        let resp = self.session.get(endpoint).send()?;
+        let resp = self.send(&Endpoint::default_save_path())?;
         Ok(resp.text()?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn cookies_round_trip() {
+        let value = json!([{
+            "name": "session",
+            "domain": "tracker.example",
+            "path": "/",
+            "value": "abc",
+            "expirationDate": 1700000000
+        }]);
+        let cookies: Vec<Cookie> = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(cookies[0].expiration_date, 1700000000);
+
+        let endpoint = Endpoint::set_cookies(&cookies).unwrap();
+        let (key, sent) = &endpoint.params[0];
+        assert_eq!(*key, "cookies");
+        assert_eq!(serde_json::from_str::<Value>(sent).unwrap(), value);
+    }
+
+    #[test]
+    fn parses_network_interfaces() {
+        let interfaces: Vec<NetworkInterface> =
+            serde_json::from_value(json!([{ "name": "Ethernet", "value": "eth0" }])).unwrap();
+        assert_eq!(interfaces[0].value, "eth0");
+    }
+}
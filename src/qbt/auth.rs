@@ -0,0 +1,330 @@
+use reqwest::StatusCode;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::qbt::core::Client;
+use crate::qbt::error::{Error, Result};
+
+/// Check the response of `auth/login`, which answers 200 "Fails." to wrong
+/// credentials and 403 to a banned IP.
+pub(super) fn check_login_response(status: StatusCode, text: &str) -> Result<()> {
+    if status == StatusCode::FORBIDDEN {
+        return Err(Error::Banned);
+    }
+    if status.is_client_error() || status.is_server_error() {
+        return Err(Error::from_status(status, text.to_string(), &[]));
+    }
+    if text == "Fails." {
+        return Err(Error::InvalidCredentials);
+    }
+    Ok(())
+}
+
+/// When the client may log in again by itself after the session expired.
+///
+/// qBittorrent bans an IP after a few failed logins (5 by default), so
+/// attempts are spaced out and stop after `max_failures` in a row.
+#[derive(Debug, Clone, Copy)]
+pub struct ReauthPolicy {
+    /// Log in again on an expired session at all
+    pub enabled: bool,
+    /// Minimum time between two attempts
+    pub min_interval: Duration,
+    /// Failed attempts in a row after which the client gives up until the
+    /// next explicit `Client::login`
+    pub max_failures: u32,
+}
+
+impl Default for ReauthPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_interval: Duration::from_secs(10),
+            max_failures: 3,
+        }
+    }
+}
+
+/// What happened when a request hit an expired session.
+#[derive(Debug)]
+pub enum ReauthEvent<'a> {
+    /// Logged in again, the request to `path` is replayed
+    Renewed { path: &'a str },
+    /// Logging in again failed, the request to `path` fails with `error`
+    Failed { path: &'a str, error: &'a Error },
+    /// The policy did not allow to log in again, the request to `path` fails
+    /// as unauthorized
+    Throttled { path: &'a str },
+}
+
+pub(super) struct ReauthHook(Box<dyn Fn(&ReauthEvent) + Send + Sync>);
+
+impl fmt::Debug for ReauthHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ReauthHook")
+    }
+}
+
+#[derive(Debug, Default)]
+pub(super) struct ReauthState {
+    policy: ReauthPolicy,
+    hook: Option<ReauthHook>,
+    last_attempt: Option<Instant>,
+    last_success: Option<Instant>,
+    failures: u32,
+    /// Set when the server refused the credentials or banned us, as trying
+    /// again can only make it worse
+    disabled: bool,
+}
+
+/// Session status shared by every clone of a client.
+///
+/// Renewing a session goes through `begin` and `finish`, which the clients
+/// must serialize so that a single login runs at a time: requests refused
+/// meanwhile then see the new session in `begin` and are only replayed.
+#[derive(Debug, Default)]
+pub(super) struct Reauth {
+    state: Mutex<ReauthState>,
+    /// Held by the blocking client while logging in again
+    login: Mutex<()>,
+}
+
+/// What to do with a request refused for an expired session.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) enum ReauthStep {
+    /// A new session was opened since the request was sent, replay it
+    Replay,
+    /// Log in, then call `Reauth::finish`
+    Login,
+}
+
+impl ReauthState {
+    fn notify(&self, event: ReauthEvent) {
+        if let Some(hook) = &self.hook {
+            (hook.0)(&event);
+        }
+    }
+}
+
+impl Reauth {
+    fn state(&self) -> MutexGuard<'_, ReauthState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Stop logging in by itself until the next explicit login.
+    pub(super) fn disable(&self) {
+        self.state().disabled = true;
+    }
+
+    /// Forget previous failures after an explicit login.
+    pub(super) fn reset(&self) {
+        let mut state = self.state();
+        state.last_attempt = None;
+        state.last_success = Some(Instant::now());
+        state.failures = 0;
+        state.disabled = false;
+    }
+
+    pub(super) fn set_policy(&self, policy: ReauthPolicy) {
+        self.state().policy = policy;
+    }
+
+    pub(super) fn set_hook<F>(&self, hook: F)
+    where
+        F: Fn(&ReauthEvent) + Send + Sync + 'static,
+    {
+        self.state().hook = Some(ReauthHook(Box::new(hook)));
+    }
+
+    /// Start renewing the session after the request to `path`, sent at
+    /// `sent_at`, was refused as unauthorized. Fails as unauthorized if the
+    /// policy does not allow to log in now.
+    pub(super) fn begin(&self, path: &str, sent_at: Instant) -> Result<ReauthStep> {
+        let mut state = self.state();
+
+        if state.last_success.is_some_and(|x| x > sent_at) {
+            // Someone else logged in since this request was sent.
+            return Ok(ReauthStep::Replay);
+        }
+
+        let policy = state.policy;
+        let too_soon = state
+            .last_attempt
+            .is_some_and(|x| x.elapsed() < policy.min_interval);
+        if !policy.enabled || state.disabled || too_soon || state.failures >= policy.max_failures {
+            state.notify(ReauthEvent::Throttled { path });
+            return Err(Error::Unauthorized);
+        }
+
+        state.last_attempt = Some(Instant::now());
+        Ok(ReauthStep::Login)
+    }
+
+    /// Record the `result` of the login started by `begin`, and return it.
+    pub(super) fn finish(&self, path: &str, result: Result<()>) -> Result<()> {
+        let mut state = self.state();
+        match result {
+            Ok(()) => {
+                state.failures = 0;
+                state.last_success = Some(Instant::now());
+                state.notify(ReauthEvent::Renewed { path });
+                Ok(())
+            }
+            Err(error) => {
+                state.failures += 1;
+                state.disabled = matches!(error, Error::Banned | Error::InvalidCredentials);
+                state.notify(ReauthEvent::Failed {
+                    path,
+                    error: &error,
+                });
+                Err(error)
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Change the re-login policy of this client and all its clones.
+    pub fn set_reauth_policy(&self, policy: ReauthPolicy) {
+        self.inner.reauth.set_policy(policy);
+    }
+
+    /// Call `hook` every time a request hits an expired session. Other
+    /// requests wait for the new session while it runs, so it must not use
+    /// the client.
+    pub fn on_reauth<F>(&self, hook: F)
+    where
+        F: Fn(&ReauthEvent) + Send + Sync + 'static,
+    {
+        self.inner.reauth.set_hook(hook);
+    }
+
+    /// Log in again after the request to `path`, sent at `sent_at`, was
+    /// refused as unauthorized. The request can be replayed on success.
+    pub(super) fn renew_session(&self, path: &str, sent_at: Instant) -> Result<()> {
+        // Held while logging in, so concurrent requests wait for a single
+        // login instead of each sending their own.
+        let reauth = &self.inner.reauth;
+        let _login = reauth.login.lock().unwrap_or_else(|e| e.into_inner());
+
+        match reauth.begin(path, sent_at)? {
+            ReauthStep::Replay => Ok(()),
+            ReauthStep::Login => reauth.finish(path, self.authenticate()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn login_response() {
+        assert!(check_login_response(StatusCode::OK, "Ok.").is_ok());
+        assert!(matches!(
+            check_login_response(StatusCode::OK, "Fails."),
+            Err(Error::InvalidCredentials)
+        ));
+        assert!(matches!(
+            check_login_response(StatusCode::FORBIDDEN, "Forbidden"),
+            Err(Error::Banned)
+        ));
+        assert!(check_login_response(StatusCode::INTERNAL_SERVER_ERROR, "").is_err());
+    }
+
+    fn reauth(policy: ReauthPolicy) -> (Reauth, Arc<Mutex<Vec<String>>>) {
+        let reauth = Reauth::default();
+        let events = Arc::new(Mutex::new(vec![]));
+        let log = events.clone();
+        reauth.set_policy(policy);
+        reauth.set_hook(move |event| {
+            let name = match event {
+                ReauthEvent::Renewed { .. } => "renewed",
+                ReauthEvent::Failed { .. } => "failed",
+                ReauthEvent::Throttled { .. } => "throttled",
+            };
+            log.lock().unwrap().push(name.to_string());
+        });
+        (reauth, events)
+    }
+
+    fn no_interval(max_failures: u32) -> ReauthPolicy {
+        ReauthPolicy {
+            min_interval: Duration::ZERO,
+            max_failures,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn renewed_once_for_concurrent_requests() {
+        let (reauth, events) = reauth(ReauthPolicy::default());
+        let sent_at = Instant::now();
+
+        assert_eq!(reauth.begin("a", sent_at).unwrap(), ReauthStep::Login);
+        assert!(reauth.finish("a", Ok(())).is_ok());
+        // Refused before the new session was opened: only replayed.
+        assert_eq!(reauth.begin("b", sent_at).unwrap(), ReauthStep::Replay);
+        assert_eq!(*events.lock().unwrap(), ["renewed"]);
+    }
+
+    #[test]
+    fn attempts_are_spaced_out() {
+        let (reauth, events) = reauth(ReauthPolicy::default());
+
+        assert_eq!(
+            reauth.begin("a", Instant::now()).unwrap(),
+            ReauthStep::Login
+        );
+        let failed = reauth.finish("a", Err(Error::Failed("timeout".to_string())));
+        assert!(failed.is_err());
+        assert!(matches!(
+            reauth.begin("a", Instant::now()),
+            Err(Error::Unauthorized)
+        ));
+        assert_eq!(*events.lock().unwrap(), ["failed", "throttled"]);
+    }
+
+    #[test]
+    fn gives_up_after_max_failures() {
+        let (reauth, _) = reauth(no_interval(2));
+        for _ in 0..2 {
+            assert_eq!(
+                reauth.begin("a", Instant::now()).unwrap(),
+                ReauthStep::Login
+            );
+            let _ = reauth.finish("a", Err(Error::Failed("timeout".to_string())));
+        }
+        assert!(reauth.begin("a", Instant::now()).is_err());
+
+        // An explicit login starts over.
+        reauth.reset();
+        assert_eq!(
+            reauth.begin("a", Instant::now()).unwrap(),
+            ReauthStep::Login
+        );
+    }
+
+    #[test]
+    fn refused_credentials_disable_reauth() {
+        let (reauth, _) = reauth(no_interval(10));
+        assert_eq!(
+            reauth.begin("a", Instant::now()).unwrap(),
+            ReauthStep::Login
+        );
+        let _ = reauth.finish("a", Err(Error::InvalidCredentials));
+        assert!(reauth.begin("a", Instant::now()).is_err());
+    }
+
+    #[test]
+    fn disabled_policy() {
+        let (reauth, events) = reauth(ReauthPolicy {
+            enabled: false,
+            ..Default::default()
+        });
+        assert!(reauth.begin("a", Instant::now()).is_err());
+        assert_eq!(*events.lock().unwrap(), ["throttled"]);
+    }
+}
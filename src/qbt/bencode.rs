@@ -0,0 +1,310 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use crate::qbt::error::{Error, Result};
+
+/// Nesting deeper than this is rejected, real torrents barely reach 10 in
+/// their v2 file tree.
+const MAX_DEPTH: usize = 256;
+
+/// A bencoded value. Dictionaries keep their keys sorted, as required by the
+/// encoding, so `encode` always produces canonical bencode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Bytes(Vec<u8>),
+    List(Vec<Value>),
+    Dict(BTreeMap<Vec<u8>, Value>),
+}
+
+impl Value {
+    /// Decode a single value spanning all of `data`.
+    pub fn decode(data: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder::new(data);
+        let value = decoder.value(0)?;
+        decoder.finish()?;
+        Ok(value)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![];
+        self.encode_to(&mut out);
+        out
+    }
+
+    pub fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            Self::Int(x) => {
+                out.push(b'i');
+                out.extend_from_slice(x.to_string().as_bytes());
+                out.push(b'e');
+            }
+            Self::Bytes(x) => encode_bytes(x, out),
+            Self::List(x) => {
+                out.push(b'l');
+                x.iter().for_each(|x| x.encode_to(out));
+                out.push(b'e');
+            }
+            Self::Dict(x) => {
+                out.push(b'd');
+                for (key, value) in x {
+                    encode_bytes(key, out);
+                    value.encode_to(out);
+                }
+                out.push(b'e');
+            }
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Self::Bytes(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Byte string as UTF-8, if it is valid.
+    pub fn as_str(&self) -> Option<&str> {
+        self.as_bytes().and_then(|x| std::str::from_utf8(x).ok())
+    }
+
+    pub fn as_list(&self) -> Option<&[Value]> {
+        match self {
+            Self::List(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    pub fn as_dict(&self) -> Option<&BTreeMap<Vec<u8>, Value>> {
+        match self {
+            Self::Dict(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Value of `key`, if this is a dictionary holding it.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_dict().and_then(|x| x.get(key.as_bytes()))
+    }
+}
+
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(bytes.len().to_string().as_bytes());
+    out.push(b':');
+    out.extend_from_slice(bytes);
+}
+
+fn invalid(message: &str, pos: usize) -> Error {
+    Error::InvalidTorrent(format!("{} at byte {}", message, pos))
+}
+
+/// Decoder remembering where the values of the top level dictionary start
+/// and end, as infohashes are computed over the original bytes.
+pub(super) struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    spans: BTreeMap<Vec<u8>, Range<usize>>,
+}
+
+impl<'a> Decoder<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            pos: 0,
+            spans: BTreeMap::new(),
+        }
+    }
+
+    /// Fail if anything is left after the decoded value.
+    pub(super) fn finish(&self) -> Result<()> {
+        if self.pos != self.data.len() {
+            return Err(invalid("Trailing data", self.pos));
+        }
+        Ok(())
+    }
+
+    /// Raw bytes of the value of `key` in the top level dictionary.
+    pub(super) fn raw(&self, key: &str) -> Option<&'a [u8]> {
+        self.spans
+            .get(key.as_bytes())
+            .map(|x| &self.data[x.clone()])
+    }
+
+    fn peek(&self) -> Result<u8> {
+        self.data
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| invalid("Unexpected end of data", self.pos))
+    }
+
+    pub(super) fn value(&mut self, depth: usize) -> Result<Value> {
+        if depth > MAX_DEPTH {
+            return Err(invalid("Nesting too deep", self.pos));
+        }
+
+        match self.peek()? {
+            b'i' => {
+                self.pos += 1;
+                let x = self.integer(b'e')?;
+                Ok(Value::Int(x))
+            }
+            b'l' => {
+                self.pos += 1;
+                let mut list = vec![];
+                while self.peek()? != b'e' {
+                    list.push(self.value(depth + 1)?);
+                }
+                self.pos += 1;
+                Ok(Value::List(list))
+            }
+            b'd' => {
+                self.pos += 1;
+                let mut dict = BTreeMap::new();
+                while self.peek()? != b'e' {
+                    if !self.peek()?.is_ascii_digit() {
+                        return Err(invalid("Dictionary key is not a string", self.pos));
+                    }
+                    let key = self.bytes()?;
+                    let start = self.pos;
+                    let value = self.value(depth + 1)?;
+                    if depth == 0 {
+                        self.spans.insert(key.clone(), start..self.pos);
+                    }
+                    if dict.insert(key, value).is_some() {
+                        return Err(invalid("Duplicate dictionary key", start));
+                    }
+                }
+                self.pos += 1;
+                Ok(Value::Dict(dict))
+            }
+            b'0'..=b'9' => Ok(Value::Bytes(self.bytes()?)),
+            _ => Err(invalid("Unexpected byte", self.pos)),
+        }
+    }
+
+    fn bytes(&mut self) -> Result<Vec<u8>> {
+        let start = self.pos;
+        let len = usize::try_from(self.integer(b':')?)
+            .map_err(|_| invalid("Negative string length", start))?;
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|x| *x <= self.data.len())
+            .ok_or_else(|| invalid("String past the end of data", start))?;
+        let bytes = self.data[self.pos..end].to_vec();
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Integer up to `terminator`, rejecting the leading zeros and "-0" the
+    /// encoding forbids.
+    fn integer(&mut self, terminator: u8) -> Result<i64> {
+        let start = self.pos;
+        let len = self.data[start..]
+            .iter()
+            .position(|x| *x == terminator)
+            .ok_or_else(|| invalid("Unterminated integer", start))?;
+        let digits = std::str::from_utf8(&self.data[start..start + len])
+            .map_err(|_| invalid("Invalid integer", start))?;
+        let unsigned = digits.strip_prefix('-').unwrap_or(digits);
+        let canonical = match unsigned.as_bytes() {
+            [] => false,
+            [b'0'] => unsigned.len() == digits.len(),
+            [b'0', ..] => false,
+            x => x.iter().all(u8::is_ascii_digit),
+        };
+        if !canonical {
+            return Err(invalid("Invalid integer", start));
+        }
+        let x = digits
+            .parse()
+            .map_err(|_| invalid("Integer out of range", start))?;
+        self.pos = start + len + 1;
+        Ok(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(data: &str) -> Result<Value> {
+        Value::decode(data.as_bytes())
+    }
+
+    #[test]
+    fn decode_values() {
+        assert_eq!(decode("i42e").unwrap(), Value::Int(42));
+        assert_eq!(decode("i-7e").unwrap(), Value::Int(-7));
+        assert_eq!(decode("i0e").unwrap(), Value::Int(0));
+        assert_eq!(decode("0:").unwrap(), Value::Bytes(vec![]));
+
+        let value = decode("d4:listl3:abci1ee3:numi3ee").unwrap();
+        assert_eq!(value.get("num").and_then(Value::as_int), Some(3));
+        let list = value.get("list").and_then(Value::as_list).unwrap();
+        assert_eq!(list[0].as_str(), Some("abc"));
+        assert_eq!(list[1].as_int(), Some(1));
+        assert!(value.get("missing").is_none());
+    }
+
+    #[test]
+    fn round_trip() {
+        for data in ["i-12e", "4:spam", "le", "de", "d1:ad1:bl0:i0eee1:ci1ee"] {
+            assert_eq!(decode(data).unwrap().encode(), data.as_bytes());
+        }
+        // Keys are sorted on encoding.
+        assert_eq!(
+            decode("d1:bi1e1:ai2ee").unwrap().encode(),
+            b"d1:ai2e1:bi1ee"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_integers() {
+        for data in [
+            "i03e", "i-0e", "i-03e", "ie", "i-e", "i1.5e", "i1", "03:abc",
+        ] {
+            assert!(decode(data).is_err(), "{}", data);
+        }
+        assert!(decode("i99999999999999999999e").is_err());
+    }
+
+    #[test]
+    fn reject_invalid_structures() {
+        for data in [
+            "i1ei2e",
+            "4:spamx",
+            "5:spam",
+            "l",
+            "d1:ai1e1:ai2ee",
+            "di1ei2ee",
+            "x",
+        ] {
+            assert!(decode(data).is_err(), "{}", data);
+        }
+    }
+
+    #[test]
+    fn nesting_depth() {
+        let nested = |depth: usize| "l".repeat(depth) + &"e".repeat(depth);
+        assert!(decode(&nested(MAX_DEPTH)).is_ok());
+        assert!(decode(&nested(MAX_DEPTH + 2)).is_err());
+    }
+
+    #[test]
+    fn raw_top_level_values() {
+        let data = b"d4:infod1:xi1ee4:name3:abce";
+        let mut decoder = Decoder::new(data);
+        decoder.value(0).unwrap();
+        assert_eq!(decoder.raw("info"), Some(b"d1:xi1ee".as_slice()));
+        assert_eq!(decoder.raw("name"), Some(b"3:abc".as_slice()));
+        assert_eq!(decoder.raw("other"), None);
+    }
+}
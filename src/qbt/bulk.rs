@@ -0,0 +1,189 @@
+use std::fmt;
+
+use crate::qbt::core::Client;
+use crate::qbt::endpoint::{start_path, stop_path, Endpoint, STOP_START_API_VERSION};
+use crate::qbt::error::Result;
+use crate::qbt::query::TorrentListQuery;
+use crate::qbt::tags::Tags;
+use crate::qbt::torrents::{Torrent, TorrentInfo};
+use crate::qbt::transfer::ByteRate;
+
+/// Set of torrents a bulk action applies to.
+pub enum TorrentSelector {
+    /// Torrents with these hashes
+    Hashes(Vec<String>),
+    /// Every torrent of the client
+    All,
+    /// Torrents matching a predicate. Resolved client side with one
+    /// `torrents/info` request before the action is sent.
+    Filter(Box<dyn Fn(&TorrentInfo) -> bool + Send + Sync>),
+}
+
+impl fmt::Debug for TorrentSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hashes(x) => f.debug_tuple("Hashes").field(x).finish(),
+            Self::All => write!(f, "All"),
+            Self::Filter(_) => write!(f, "Filter(..)"),
+        }
+    }
+}
+
+impl TorrentSelector {
+    pub fn filter<F>(predicate: F) -> Self
+    where
+        F: Fn(&TorrentInfo) -> bool + Send + Sync + 'static,
+    {
+        Self::Filter(Box::new(predicate))
+    }
+
+    /// Whether `hashes` needs the current torrent list.
+    pub(super) fn needs_torrent_list(&self) -> bool {
+        matches!(self, Self::Filter(_))
+    }
+
+    /// Value of the `hashes` field, or `None` if the selector matches no
+    /// torrent. `torrents` is only read if `needs_torrent_list` is true.
+    pub(super) fn hashes(&self, torrents: &[TorrentInfo]) -> Option<String> {
+        let hashes = match self {
+            Self::All => "all".to_string(),
+            Self::Hashes(x) => x.join("|"),
+            Self::Filter(predicate) => {
+                let hashes: Vec<&str> = torrents
+                    .iter()
+                    .filter(|x| predicate(x))
+                    .map(|x| x.hash.as_str())
+                    .collect();
+                hashes.join("|")
+            }
+        };
+        (!hashes.is_empty()).then_some(hashes)
+    }
+}
+
+impl From<&Torrent> for TorrentSelector {
+    fn from(torrent: &Torrent) -> Self {
+        Self::Hashes(vec![torrent.info.hash.clone()])
+    }
+}
+
+impl From<&[Torrent]> for TorrentSelector {
+    fn from(torrents: &[Torrent]) -> Self {
+        Self::Hashes(torrents.iter().map(|x| x.info.hash.clone()).collect())
+    }
+}
+
+impl Client {
+    /// POST `endpoint` for every torrent of `selector` in a single request,
+    /// with `params` as extra form fields.
+    pub(super) fn torrents_action(
+        &self,
+        endpoint: &'static str,
+        selector: &TorrentSelector,
+        params: &[(&'static str, &str)],
+    ) -> Result<()> {
+        let torrents = match selector.needs_torrent_list() {
+            true => self.query_torrent_infos(&TorrentListQuery::new())?,
+            false => vec![],
+        };
+        let Some(hashes) = selector.hashes(&torrents) else {
+            return Ok(());
+        };
+
+        self.send(&Endpoint::torrents_action(endpoint, &hashes).params(params))?;
+        Ok(())
+    }
+
+    pub fn pause_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        let stop_start = self.api_version_at_least(STOP_START_API_VERSION)?;
+        self.torrents_action(stop_path(stop_start), selector, &[])
+    }
+
+    pub fn resume_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        let stop_start = self.api_version_at_least(STOP_START_API_VERSION)?;
+        self.torrents_action(start_path(stop_start), selector, &[])
+    }
+
+    /// Remove the torrents, and their downloaded data if `delete_files` is set.
+    pub fn delete_torrents(&self, selector: &TorrentSelector, delete_files: bool) -> Result<()> {
+        let delete_files = delete_files.to_string();
+        self.torrents_action(
+            "torrents/delete",
+            selector,
+            &[("deleteFiles", &delete_files)],
+        )
+    }
+
+    pub fn recheck_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        self.torrents_action("torrents/recheck", selector, &[])
+    }
+
+    pub fn reannounce_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        self.torrents_action("torrents/reannounce", selector, &[])
+    }
+
+    /// Set the category of the torrents. An empty `category` resets it.
+    pub fn set_torrents_category(&self, selector: &TorrentSelector, category: &str) -> Result<()> {
+        self.torrents_action("torrents/setCategory", selector, &[("category", category)])
+    }
+
+    pub fn add_torrents_tags(&self, selector: &TorrentSelector, tags: &Tags) -> Result<()> {
+        let tags = tags.to_string();
+        self.torrents_action("torrents/addTags", selector, &[("tags", &tags)])
+    }
+
+    /// Set the download limit of the torrents.
+    pub fn set_torrents_download_limit(
+        &self,
+        selector: &TorrentSelector,
+        limit: ByteRate,
+    ) -> Result<()> {
+        let limit = limit.to_string();
+        self.torrents_action("torrents/setDownloadLimit", selector, &[("limit", &limit)])
+    }
+
+    /// Set the upload limit of the torrents.
+    pub fn set_torrents_upload_limit(
+        &self,
+        selector: &TorrentSelector,
+        limit: ByteRate,
+    ) -> Result<()> {
+        let limit = limit.to_string();
+        self.torrents_action("torrents/setUploadLimit", selector, &[("limit", &limit)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qbt::torrents::tests::torrent_info;
+
+    fn info(hash: &str, progress: f32) -> TorrentInfo {
+        TorrentInfo {
+            progress,
+            ..torrent_info(hash)
+        }
+    }
+
+    #[test]
+    fn static_selectors() {
+        assert!(!TorrentSelector::All.needs_torrent_list());
+        assert_eq!(TorrentSelector::All.hashes(&[]).unwrap(), "all");
+
+        let selector = TorrentSelector::Hashes(vec!["a".to_string(), "b".to_string()]);
+        assert!(!selector.needs_torrent_list());
+        assert_eq!(selector.hashes(&[]).unwrap(), "a|b");
+        assert_eq!(TorrentSelector::Hashes(vec![]).hashes(&[]), None);
+    }
+
+    #[test]
+    fn filter_selector() {
+        let torrents = [info("a", 1.0), info("b", 0.5), info("c", 1.0)];
+        let selector = TorrentSelector::filter(|x| x.progress >= 1.0);
+        assert!(selector.needs_torrent_list());
+        assert_eq!(selector.hashes(&torrents).unwrap(), "a|c");
+
+        let selector = TorrentSelector::filter(|_| false);
+        assert_eq!(selector.hashes(&torrents), None);
+    }
+}
@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::qbt::bulk::TorrentSelector;
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
+use crate::qbt::torrents::Torrent;
+
+/// Download path of a category, when it overrides the global setting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CategoryDownloadPath {
+    /// Incomplete torrents are kept in this path
+    Path(String),
+    /// Always `false`: the download path is disabled for this category. The
+    /// value is ignored when writing.
+    Disabled(bool),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Category {
+    /// Category name
+    pub name: String,
+    /// Save path of the torrents in this category. Empty for the default one.
+    #[serde(rename = "savePath")]
+    pub save_path: String,
+    /// Download path override, `None` to use the global setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_path: Option<CategoryDownloadPath>,
+}
+
+impl Client {
+    /// Every category, by name.
+    pub fn get_categories(&self) -> Result<HashMap<String, Category>> {
+        let resp = self.send(&Endpoint::categories())?;
+        let result: HashMap<String, Category> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Create a category. An empty `save_path` uses the default one, and
+    /// `download_path` overrides the global download path, `None` following
+    /// the global setting.
+    pub fn create_category(
+        &self,
+        name: &str,
+        save_path: &str,
+        download_path: Option<CategoryDownloadPath>,
+    ) -> Result<()> {
+        let endpoint = Endpoint::category(
+            "torrents/createCategory",
+            name,
+            save_path,
+            download_path.as_ref(),
+        );
+        self.send(&endpoint)?;
+        Ok(())
+    }
+
+    /// Change the paths of an existing category. A `download_path` of `None`
+    /// resets it to the global setting.
+    pub fn edit_category(
+        &self,
+        name: &str,
+        save_path: &str,
+        download_path: Option<CategoryDownloadPath>,
+    ) -> Result<()> {
+        let endpoint = Endpoint::category(
+            "torrents/editCategory",
+            name,
+            save_path,
+            download_path.as_ref(),
+        );
+        self.send(&endpoint)?;
+        Ok(())
+    }
+
+    pub fn remove_categories(&self, names: &[&str]) -> Result<()> {
+        self.send(&Endpoint::remove_categories(names))?;
+        Ok(())
+    }
+}
+
+impl Torrent {
+    /// Set the category of this torrent. An empty `category` resets it.
+    pub fn set_category(&self, category: &str) -> Result<()> {
+        self.client
+            .set_torrents_category(&TorrentSelector::from(self), category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_download_path_overrides() {
+        let categories: HashMap<String, Category> = serde_json::from_str(
+            r#"{
+                "a": {"name": "a", "savePath": "/a"},
+                "b": {"name": "b", "savePath": "", "download_path": false},
+                "c": {"name": "c", "savePath": "/c", "download_path": "/tmp/c"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(categories["a"].download_path, None);
+        assert_eq!(
+            categories["b"].download_path,
+            Some(CategoryDownloadPath::Disabled(false))
+        );
+        assert_eq!(
+            categories["c"].download_path,
+            Some(CategoryDownloadPath::Path("/tmp/c".to_string()))
+        );
+    }
+
+    #[test]
+    fn writes_download_path_overrides() {
+        let params = |x: Option<&CategoryDownloadPath>| {
+            Endpoint::category("torrents/editCategory", "a", "/a", x).params
+        };
+        let base = [
+            ("category", "a".to_string()),
+            ("savePath", "/a".to_string()),
+        ];
+
+        assert_eq!(params(None), base);
+        let disabled = CategoryDownloadPath::Disabled(false);
+        assert_eq!(
+            params(Some(&disabled))[2..],
+            [("downloadPathEnabled", "false".to_string())]
+        );
+        let path = CategoryDownloadPath::Path("/tmp/a".to_string());
+        assert_eq!(
+            params(Some(&path))[2..],
+            [
+                ("downloadPathEnabled", "true".to_string()),
+                ("downloadPath", "/tmp/a".to_string()),
+            ]
+        );
+    }
+}
@@ -1,9 +1,16 @@
-use anyhow::{bail, Context, Result};
 use reqwest::{
     self,
+    blocking::{RequestBuilder, Response},
     header::{self, HeaderMap},
+    StatusCode,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::qbt::auth::{check_login_response, Reauth};
+use crate::qbt::endpoint::{ApiVersion, Endpoint, Method};
+use crate::qbt::error::{Error, Result, StatusTable};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub(super) struct Credentials {
@@ -11,21 +18,92 @@ pub(super) struct Credentials {
     password: String,
 }
 
-#[derive(Debug)]
+/// Handle to a qBittorrent Web API session.
+///
+/// Cloning is cheap and every clone shares the same session, so torrents and
+/// files keep their own handle instead of borrowing the client.
+#[derive(Debug, Clone)]
 pub struct Client {
+    pub(super) inner: Arc<ClientInner>,
+}
+
+#[derive(Debug)]
+pub(super) struct ClientInner {
     pub(super) base_url: String,
     pub(super) credentials: Credentials,
     pub(super) session: reqwest::blocking::Client,
+    /// Lazily fetched Web API version, see `Client::api_version_at_least`.
+    pub(super) api_version: ApiVersion,
+    /// Transparent login on expired sessions, see `Client::renew_session`.
+    pub(super) reauth: Reauth,
+}
+
+impl Credentials {
+    pub(super) fn new(username: &str, password: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    pub(super) fn login_endpoint(&self) -> Endpoint {
+        Endpoint::login(&self.username, &self.password)
+    }
 }
 
 impl Client {
     pub(super) fn url(&self, endpoint: &str) -> String {
-        format!("{}/{}", self.base_url, endpoint)
+        format!("{}/{}", self.inner.base_url, endpoint)
+    }
+
+    fn request(&self, endpoint: &Endpoint) -> RequestBuilder {
+        let url = self.url(endpoint.path);
+        match endpoint.method {
+            Method::Get => self.inner.session.get(url).query(&endpoint.params),
+            Method::Post => self.inner.session.post(url).form(&endpoint.params),
+        }
+    }
+
+    /// Send `endpoint`, failing on HTTP error statuses.
+    pub fn send(&self, endpoint: &Endpoint) -> Result<Response> {
+        self.execute(endpoint, || Ok(self.request(endpoint)))
+    }
+
+    /// Send the request to `endpoint` made by `build`. If the session
+    /// expired, log in again once and replay it with a new request from
+    /// `build`.
+    pub(super) fn execute<F>(&self, endpoint: &Endpoint, build: F) -> Result<Response>
+    where
+        F: Fn() -> Result<RequestBuilder>,
+    {
+        let sent_at = Instant::now();
+        let resp = build()?.send()?;
+        match resp.status() {
+            StatusCode::UNAUTHORIZED => {}
+            // Also sent by endpoints refusing the request, e.g. setLocation
+            // without write access, which must not log in again.
+            StatusCode::FORBIDDEN => {
+                let body = resp.text().unwrap_or_default();
+                if !Error::is_session_status(StatusCode::FORBIDDEN, &body) {
+                    return Err(Error::from_status(
+                        StatusCode::FORBIDDEN,
+                        body,
+                        endpoint.errors,
+                    ));
+                }
+            }
+            _ => return check_status(resp, endpoint.errors),
+        }
+
+        self.renew_session(endpoint.path, sent_at)?;
+        check_status(build()?.send()?, endpoint.errors)
     }
 
     pub fn new(base_url: &str, username: &str, password: &str, ssl_verify: bool) -> Result<Client> {
         let mut headers = HeaderMap::new();
-        headers.append("Referer", header::HeaderValue::from_str(base_url)?);
+        let referer = header::HeaderValue::from_str(base_url)
+            .map_err(|_| Error::InvalidRequest(format!("Invalid base URL: {}", base_url)))?;
+        headers.append("Referer", referer);
 
         let session = reqwest::blocking::ClientBuilder::new()
             .danger_accept_invalid_certs(!ssl_verify)
@@ -33,46 +111,55 @@ impl Client {
             .cookie_store(true)
             .build()?;
         let base_url = format!("{}/api/v2", base_url);
-        Ok(Self {
+        let inner = ClientInner {
             base_url,
-            credentials: Credentials {
-                username: username.to_string(),
-                password: password.to_string(),
-            },
+            credentials: Credentials::new(username, password),
             session,
+            api_version: ApiVersion::default(),
+            reauth: Reauth::default(),
+        };
+        Ok(Self {
+            inner: Arc::new(inner),
         })
     }
 
-    pub fn login(&mut self) -> Result<()> {
-        let endpoint = self.url("auth/login");
-        let resp = self.session.post(endpoint).form(&self.credentials).send()?;
-
-        println!("Response code: {}", resp.status());
+    pub fn login(&self) -> Result<()> {
+        self.authenticate()?;
+        self.inner.reauth.reset();
+        Ok(())
+    }
 
-        match resp.error_for_status_ref() {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                let status = e.status();
-                if status.is_some_and(|x| x == 403) {
-                    Err(e).with_context(|| "User's IP is banned for too many failed login attempts")
-                } else {
-                    Err(e).with_context(|| "Failed to login")
-                }
-            }
-        }?;
+    /// Open a new session with the stored credentials.
+    pub(super) fn authenticate(&self) -> Result<()> {
+        let endpoint = self.inner.credentials.login_endpoint();
+        let resp = self.request(&endpoint).send()?;
+        let status = resp.status();
+        check_login_response(status, &resp.text()?)
+    }
 
-        let text = resp.text()?;
-        if text == "Fails." {
-            bail!("{}: Invalid credentials", text)
-        }
+    /// Close the session. Later requests fail as unauthorized instead of
+    /// logging in again, until the next `login`.
+    pub fn logout(&self) -> Result<()> {
+        self.send(&Endpoint::logout())?;
+        self.inner.reauth.disable();
 
         Ok(())
     }
+}
 
-    pub fn logout(&mut self) -> Result<()> {
-        let endpoint = self.url("auth/logout");
-        self.session.post(endpoint).send()?;
-
-        Ok(())
+/// Turn an HTTP error status into the matching `Error` of the endpoint's
+/// `errors`, with the response body as message.
+pub(super) fn check_status(resp: Response, errors: StatusTable) -> Result<Response> {
+    let status = resp.status();
+    if status.is_client_error() || status.is_server_error() {
+        let message = resp.text().unwrap_or_default();
+        return Err(Error::from_status(status, message, errors));
     }
+    Ok(resp)
+}
+
+/// Parse the body of `resp`, whose status `send` checked, as JSON.
+pub(super) fn parse_json<T: DeserializeOwned>(resp: Response) -> Result<T> {
+    let text = resp.text()?;
+    Ok(serde_json::from_str(&text)?)
 }
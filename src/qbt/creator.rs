@@ -0,0 +1,303 @@
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
+use strum_macros::AsRefStr;
+
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::{Error, Result};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, AsRefStr)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TorrentFormat {
+    V1,
+    V2,
+    Hybrid,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreatorTaskState {
+    Queued,
+    Running,
+    Finished,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatorTaskStatus {
+    /// Task id
+    #[serde(rename = "taskID")]
+    pub task_id: String,
+    /// Path of the file or folder the torrent is created from
+    pub source_path: String,
+    /// Current state of the task
+    pub status: CreatorTaskState,
+    /// Progress (percentage), while running
+    #[serde(default)]
+    pub progress: f32,
+    /// Reason of the failure, if any
+    #[serde(default)]
+    pub error_message: String,
+    /// Path the .torrent file is saved to on the server, if any
+    #[serde(default)]
+    pub torrent_file_path: String,
+}
+
+/// Builder for `torrentcreator/addTask`.
+#[derive(Debug, Clone)]
+pub struct CreateTorrentRequest {
+    source_path: String,
+    torrent_file_path: Option<String>,
+    piece_size: Option<u64>,
+    private: bool,
+    start_seeding: bool,
+    format: Option<TorrentFormat>,
+    trackers: Vec<String>,
+    url_seeds: Vec<String>,
+    comment: Option<String>,
+    source: Option<String>,
+}
+
+impl CreateTorrentRequest {
+    /// Create a torrent of the file or folder at `source_path`, on the server.
+    pub fn new(source_path: &str) -> Self {
+        Self {
+            source_path: source_path.to_string(),
+            torrent_file_path: None,
+            piece_size: None,
+            private: false,
+            start_seeding: false,
+            format: None,
+            trackers: vec![],
+            url_seeds: vec![],
+            comment: None,
+            source: None,
+        }
+    }
+
+    /// Also save the .torrent file to this path on the server
+    pub fn torrent_file_path(mut self, path: &str) -> Self {
+        self.torrent_file_path = Some(path.to_string());
+        self
+    }
+
+    /// Piece size (bytes). Picked automatically if unset.
+    pub fn piece_size(mut self, size: u64) -> Self {
+        self.piece_size = Some(size);
+        self
+    }
+
+    pub fn private(mut self, value: bool) -> Self {
+        self.private = value;
+        self
+    }
+
+    /// Add the created torrent to the client, seeding from `source_path`
+    pub fn start_seeding(mut self, value: bool) -> Self {
+        self.start_seeding = value;
+        self
+    }
+
+    pub fn format(mut self, format: TorrentFormat) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// Add a tracker to the current tier. All trackers share one tier
+    /// unless [`next_tier`](Self::next_tier) is called in between.
+    pub fn tracker(mut self, url: &str) -> Self {
+        self.trackers.push(url.to_string());
+        self
+    }
+
+    /// Put the trackers added after this in a new tier
+    pub fn next_tier(mut self) -> Self {
+        // The server splits tiers on empty entries
+        self.trackers.push(String::new());
+        self
+    }
+
+    pub fn web_seed(mut self, url: &str) -> Self {
+        self.url_seeds.push(url.to_string());
+        self
+    }
+
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Source field of the info dictionary, used by private trackers
+    pub fn source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    pub(super) fn form(&self) -> Vec<(&'static str, String)> {
+        let mut form = vec![
+            ("sourcePath", self.source_path.clone()),
+            ("private", self.private.to_string()),
+            ("startSeeding", self.start_seeding.to_string()),
+        ];
+        if let Some(x) = &self.torrent_file_path {
+            form.push(("torrentFilePath", x.clone()));
+        }
+        if let Some(x) = self.piece_size {
+            form.push(("pieceSize", x.to_string()));
+        }
+        if let Some(x) = self.format {
+            form.push(("format", x.as_ref().to_string()));
+        }
+        if !self.trackers.is_empty() {
+            form.push(("trackers", self.trackers.join("|")));
+        }
+        if !self.url_seeds.is_empty() {
+            form.push(("urlSeeds", self.url_seeds.join("|")));
+        }
+        if let Some(x) = &self.comment {
+            form.push(("comment", x.clone()));
+        }
+        if let Some(x) = &self.source {
+            form.push(("source", x.clone()));
+        }
+        form
+    }
+}
+
+#[derive(Deserialize)]
+struct CreatorTask {
+    #[serde(rename = "taskID")]
+    task_id: String,
+}
+
+impl Client {
+    /// Queue a torrent creation task and return its id.
+    pub fn add_creator_task(&self, request: &CreateTorrentRequest) -> Result<String> {
+        let resp = self.send(&Endpoint::creator_add_task(request))?;
+        let task: CreatorTask = parse_json(resp)?;
+        Ok(task.task_id)
+    }
+
+    /// Status of the task `task_id`, or of every task if `task_id` is `None`.
+    pub fn get_creator_status(&self, task_id: Option<&str>) -> Result<Vec<CreatorTaskStatus>> {
+        let resp = self.send(&Endpoint::creator_status(task_id))?;
+        let result: Vec<CreatorTaskStatus> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Contents of the .torrent file created by a finished task.
+    pub fn get_creator_torrent_file(&self, task_id: &str) -> Result<Vec<u8>> {
+        let resp = self.send(&Endpoint::creator_torrent_file(task_id))?;
+        Ok(resp.bytes()?.to_vec())
+    }
+
+    pub fn delete_creator_task(&self, task_id: &str) -> Result<()> {
+        self.send(&Endpoint::creator_delete_task(task_id))?;
+        Ok(())
+    }
+
+    /// Create a torrent, waiting for the task to finish, and return the
+    /// contents of the .torrent file. The task is deleted afterwards.
+    pub fn create_torrent(
+        &self,
+        request: &CreateTorrentRequest,
+        poll_interval: Duration,
+    ) -> Result<Vec<u8>> {
+        let task_id = self.add_creator_task(request)?;
+        let result = self.wait_creator_task(&task_id, poll_interval);
+        // A failed cleanup must not hide why the task itself failed.
+        let deleted = self.delete_creator_task(&task_id);
+        let torrent = result?;
+        deleted?;
+        Ok(torrent)
+    }
+
+    fn wait_creator_task(&self, task_id: &str, poll_interval: Duration) -> Result<Vec<u8>> {
+        loop {
+            let status = self
+                .get_creator_status(Some(task_id))?
+                .into_iter()
+                .next()
+                .ok_or_else(|| {
+                    Error::Unknown(format!("Unknown torrent creator task: {}", task_id))
+                })?;
+
+            match status.status {
+                CreatorTaskState::Finished => return self.get_creator_torrent_file(task_id),
+                CreatorTaskState::Failed => {
+                    let message = format!("Failed to create torrent: {}", status.error_message);
+                    return Err(Error::Failed(message));
+                }
+                CreatorTaskState::Queued | CreatorTaskState::Running => {
+                    thread::sleep(poll_interval)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn form_fields() {
+        let request = CreateTorrentRequest::new("/data/iso")
+            .private(true)
+            .format(TorrentFormat::Hybrid)
+            .tracker("udp://a.example:1337")
+            .tracker("https://b.example/announce")
+            .web_seed("https://mirror.example/iso")
+            .source("EX");
+        assert_eq!(
+            request.form(),
+            [
+                ("sourcePath", "/data/iso".to_string()),
+                ("private", "true".to_string()),
+                ("startSeeding", "false".to_string()),
+                ("format", "hybrid".to_string()),
+                (
+                    "trackers",
+                    "udp://a.example:1337|https://b.example/announce".to_string()
+                ),
+                ("urlSeeds", "https://mirror.example/iso".to_string()),
+                ("source", "EX".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn tracker_tiers() {
+        let request = CreateTorrentRequest::new("/data/iso")
+            .tracker("udp://a.example:1337")
+            .tracker("udp://b.example:1337")
+            .next_tier()
+            .tracker("https://c.example/announce");
+        let trackers = request.form().into_iter().find(|(k, _)| *k == "trackers");
+        assert_eq!(
+            trackers,
+            Some((
+                "trackers",
+                "udp://a.example:1337|udp://b.example:1337||https://c.example/announce".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn parses_status() {
+        let status: Vec<CreatorTaskStatus> = serde_json::from_value(json!([{
+            "taskID": "f1e2",
+            "sourcePath": "/data/iso",
+            "status": "Failed",
+            "errorMessage": "No such file"
+        }]))
+        .unwrap();
+        assert_eq!(status[0].task_id, "f1e2");
+        assert_eq!(status[0].status, CreatorTaskState::Failed);
+        assert_eq!(status[0].error_message, "No such file");
+        assert_eq!(status[0].torrent_file_path, "");
+    }
+}
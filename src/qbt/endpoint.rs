@@ -0,0 +1,701 @@
+use reqwest::StatusCode;
+use serde_json::{Map, Value};
+use std::sync::OnceLock;
+
+use crate::qbt::application::Cookie;
+use crate::qbt::categories::CategoryDownloadPath;
+use crate::qbt::creator::CreateTorrentRequest;
+use crate::qbt::error::{Error, Result, StatusTable};
+use crate::qbt::log::LogLevel;
+use crate::qbt::query::TorrentListQuery;
+use crate::qbt::rss::RssRule;
+use crate::qbt::tags::Tags;
+use crate::qbt::transfer::ByteRate;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Parameters are sent in the query string
+    Get,
+    /// Parameters are sent as an url-encoded form
+    Post,
+}
+
+/// A Web API call, independent of the HTTP client used to send it. Both the
+/// blocking `Client` and the async `AsyncClient` send these, so each endpoint
+/// is only described once.
+#[derive(Debug, Clone)]
+pub struct Endpoint {
+    pub method: Method,
+    /// Path below `/api/v2`, e.g. "torrents/info"
+    pub path: &'static str,
+    pub params: Vec<(&'static str, String)>,
+    /// Error statuses documented for `path`, see `Error::from_status`
+    pub errors: StatusTable,
+}
+
+impl Endpoint {
+    pub fn get(path: &'static str) -> Self {
+        Self {
+            method: Method::Get,
+            path,
+            params: vec![],
+            errors: documented_errors(path),
+        }
+    }
+
+    pub fn post(path: &'static str) -> Self {
+        Self {
+            method: Method::Post,
+            path,
+            params: vec![],
+            errors: documented_errors(path),
+        }
+    }
+
+    /// Replace the error statuses documented for the endpoint.
+    pub fn errors(mut self, errors: StatusTable) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    pub fn param<T: ToString>(mut self, key: &'static str, value: T) -> Self {
+        self.params.push((key, value.to_string()));
+        self
+    }
+
+    pub fn params(mut self, params: &[(&'static str, &str)]) -> Self {
+        for (key, value) in params {
+            self.params.push((key, value.to_string()));
+        }
+        self
+    }
+
+    /* Authentication */
+
+    pub fn login(username: &str, password: &str) -> Self {
+        Self::post("auth/login")
+            .param("username", username)
+            .param("password", password)
+    }
+
+    pub fn logout() -> Self {
+        Self::post("auth/logout")
+    }
+
+    /* Application */
+
+    pub fn version() -> Self {
+        Self::get("app/version")
+    }
+
+    pub fn webapi_version() -> Self {
+        Self::get("app/webapiVersion")
+    }
+
+    pub fn build_info() -> Self {
+        Self::get("app/buildInfo")
+    }
+
+    pub fn default_save_path() -> Self {
+        Self::get("app/defaultSavePath")
+    }
+
+    pub fn preferences() -> Self {
+        Self::get("app/preferences")
+    }
+
+    pub fn set_preferences(changes: &Map<String, Value>) -> Result<Self> {
+        let json = serde_json::to_string(changes)?;
+        Ok(Self::post("app/setPreferences").param("json", json))
+    }
+
+    pub fn shutdown() -> Self {
+        Self::post("app/shutdown")
+    }
+
+    pub fn cookies() -> Self {
+        Self::get("app/cookies")
+    }
+
+    pub fn set_cookies(cookies: &[Cookie]) -> Result<Self> {
+        let cookies = serde_json::to_string(cookies)?;
+        Ok(Self::post("app/setCookies").param("cookies", cookies))
+    }
+
+    pub fn network_interfaces() -> Self {
+        Self::get("app/networkInterfaceList")
+    }
+
+    pub fn network_interface_addresses(iface: &str) -> Self {
+        Self::get("app/networkInterfaceAddressList").param("iface", iface)
+    }
+
+    pub fn send_test_email() -> Self {
+        Self::post("app/sendTestEmail")
+    }
+
+    /* Transfer */
+
+    pub fn transfer_info() -> Self {
+        Self::get("transfer/info")
+    }
+
+    pub fn speed_limits_mode() -> Self {
+        Self::get("transfer/speedLimitsMode")
+    }
+
+    pub fn toggle_speed_limits_mode() -> Self {
+        Self::post("transfer/toggleSpeedLimitsMode")
+    }
+
+    pub fn download_limit() -> Self {
+        Self::get("transfer/downloadLimit")
+    }
+
+    pub fn set_download_limit(limit: ByteRate) -> Self {
+        Self::post("transfer/setDownloadLimit").param("limit", limit)
+    }
+
+    pub fn upload_limit() -> Self {
+        Self::get("transfer/uploadLimit")
+    }
+
+    pub fn set_upload_limit(limit: ByteRate) -> Self {
+        Self::post("transfer/setUploadLimit").param("limit", limit)
+    }
+
+    /// Ban peers, given as "host:port".
+    pub fn ban_peers(peers: &[&str]) -> Self {
+        Self::post("transfer/banPeers").param("peers", peers.join("|"))
+    }
+
+    /* Torrents */
+
+    /// `torrents/add`. The clients send it as a multipart form built from an
+    /// `AddTorrentRequest`, `params` stays empty.
+    pub fn add_torrents() -> Self {
+        Self::post("torrents/add")
+    }
+
+    pub fn torrent_list() -> Self {
+        Self::get("torrents/info")
+    }
+
+    /// `torrents/info` filtered by `query`, see `TorrentListQuery::params`.
+    pub fn torrent_query(query: &TorrentListQuery, stop_start: bool) -> Self {
+        let mut endpoint = Self::torrent_list();
+        endpoint.params.extend(query.params(stop_start));
+        endpoint
+    }
+
+    pub fn torrent_properties(hash: &str) -> Self {
+        Self::get("torrents/properties").param("hash", hash)
+    }
+
+    pub fn torrent_trackers(hash: &str) -> Self {
+        Self::get("torrents/trackers").param("hash", hash)
+    }
+
+    pub fn torrent_webseeds(hash: &str) -> Self {
+        Self::get("torrents/webseeds").param("hash", hash)
+    }
+
+    /// Files of a torrent, or only the one at `index` if set.
+    pub fn torrent_files(hash: &str, index: Option<u64>) -> Self {
+        let endpoint = Self::get("torrents/files").param("hash", hash);
+        match index {
+            Some(x) => endpoint.param("indexes", x),
+            None => endpoint,
+        }
+    }
+
+    pub fn piece_states(hash: &str) -> Self {
+        Self::get("torrents/pieceStates").param("hash", hash)
+    }
+
+    pub fn piece_hashes(hash: &str) -> Self {
+        Self::get("torrents/pieceHashes").param("hash", hash)
+    }
+
+    /// Action on the torrents of `hashes` ("all" or '|' separated hashes).
+    pub fn torrents_action(path: &'static str, hashes: &str) -> Self {
+        Self::post(path).param("hashes", hashes)
+    }
+
+    /// Action on a single torrent, for endpoints taking a `hash` field.
+    pub fn torrent_action(path: &'static str, hash: &str) -> Self {
+        Self::post(path).param("hash", hash)
+    }
+
+    /// Unlike setLocation, `torrents/setDownloadPath` takes the torrents as an
+    /// `id` field ('|' separated hashes).
+    pub fn set_download_path(hashes: &str, path: &str) -> Self {
+        Self::post("torrents/setDownloadPath")
+            .param("id", hashes)
+            .param("path", path)
+    }
+
+    pub fn categories() -> Self {
+        Self::get("torrents/categories")
+    }
+
+    /// `torrents/createCategory` or `torrents/editCategory`. Leaving out the
+    /// download path flag makes the category follow the global setting.
+    pub fn category(
+        path: &'static str,
+        name: &str,
+        save_path: &str,
+        download_path: Option<&CategoryDownloadPath>,
+    ) -> Self {
+        let endpoint = Self::post(path)
+            .param("category", name)
+            .param("savePath", save_path);
+        match download_path {
+            Some(CategoryDownloadPath::Path(x)) => endpoint
+                .param("downloadPathEnabled", true)
+                .param("downloadPath", x),
+            Some(CategoryDownloadPath::Disabled(_)) => endpoint.param("downloadPathEnabled", false),
+            None => endpoint,
+        }
+    }
+
+    pub fn remove_categories(names: &[&str]) -> Self {
+        Self::post("torrents/removeCategories").param("categories", names.join("\n"))
+    }
+
+    pub fn tags() -> Self {
+        Self::get("torrents/tags")
+    }
+
+    pub fn create_tags(tags: &Tags) -> Self {
+        Self::post("torrents/createTags").param("tags", tags)
+    }
+
+    pub fn delete_tags(tags: &Tags) -> Self {
+        Self::post("torrents/deleteTags").param("tags", tags)
+    }
+
+    /* Sync */
+
+    pub fn maindata(rid: i64) -> Self {
+        Self::get("sync/maindata").param("rid", rid)
+    }
+
+    pub fn torrent_peers(hash: &str, rid: i64) -> Self {
+        Self::get("sync/torrentPeers")
+            .param("hash", hash)
+            .param("rid", rid)
+    }
+
+    /* Log */
+
+    pub fn main_log(levels: &[LogLevel], last_known_id: Option<i64>) -> Self {
+        // No level at all would never return anything, take it as all levels.
+        let has = |x| levels.is_empty() || levels.contains(&x);
+        Self::get("log/main")
+            .param("normal", has(LogLevel::Normal))
+            .param("info", has(LogLevel::Info))
+            .param("warning", has(LogLevel::Warning))
+            .param("critical", has(LogLevel::Critical))
+            .param("last_known_id", last_known_id.unwrap_or(-1))
+    }
+
+    pub fn peer_log(last_known_id: Option<i64>) -> Self {
+        Self::get("log/peers").param("last_known_id", last_known_id.unwrap_or(-1))
+    }
+
+    /* RSS */
+
+    pub fn rss_items(with_data: bool) -> Self {
+        Self::get("rss/items").param("withData", with_data)
+    }
+
+    pub fn rss_add_folder(path: &str) -> Self {
+        Self::post("rss/addFolder").param("path", path)
+    }
+
+    pub fn rss_add_feed(url: &str, path: &str) -> Self {
+        Self::post("rss/addFeed")
+            .param("url", url)
+            .param("path", path)
+    }
+
+    pub fn rss_remove_item(path: &str) -> Self {
+        Self::post("rss/removeItem").param("path", path)
+    }
+
+    pub fn rss_move_item(item_path: &str, dest_path: &str) -> Self {
+        Self::post("rss/moveItem")
+            .param("itemPath", item_path)
+            .param("destPath", dest_path)
+    }
+
+    pub fn rss_refresh_item(item_path: &str) -> Self {
+        Self::post("rss/refreshItem").param("itemPath", item_path)
+    }
+
+    pub fn rss_mark_as_read(item_path: &str, article_id: Option<&str>) -> Self {
+        let endpoint = Self::post("rss/markAsRead").param("itemPath", item_path);
+        match article_id {
+            Some(x) => endpoint.param("articleId", x),
+            None => endpoint,
+        }
+    }
+
+    pub fn rss_rules() -> Self {
+        Self::get("rss/rules")
+    }
+
+    pub fn rss_set_rule(name: &str, rule: &RssRule) -> Result<Self> {
+        let rule = serde_json::to_string(rule)?;
+        Ok(Self::post("rss/setRule")
+            .param("ruleName", name)
+            .param("ruleDef", rule))
+    }
+
+    pub fn rss_rename_rule(name: &str, new_name: &str) -> Self {
+        Self::post("rss/renameRule")
+            .param("ruleName", name)
+            .param("newRuleName", new_name)
+    }
+
+    pub fn rss_remove_rule(name: &str) -> Self {
+        Self::post("rss/removeRule").param("ruleName", name)
+    }
+
+    pub fn rss_matching_articles(name: &str) -> Self {
+        Self::get("rss/matchingArticles").param("ruleName", name)
+    }
+
+    /* Search */
+
+    pub fn search_start(pattern: &str, plugins: &[&str], category: &str) -> Self {
+        Self::post("search/start")
+            .param("pattern", pattern)
+            .param("plugins", plugins.join("|"))
+            .param("category", category)
+    }
+
+    pub fn search_stop(id: i64) -> Self {
+        Self::post("search/stop").param("id", id)
+    }
+
+    /// Status of the search job `id`, or of every job if `id` is `None`.
+    pub fn search_status(id: Option<i64>) -> Self {
+        let endpoint = Self::get("search/status");
+        match id {
+            Some(x) => endpoint.param("id", x),
+            None => endpoint,
+        }
+    }
+
+    pub fn search_results(id: i64, limit: Option<i64>, offset: Option<i64>) -> Self {
+        let mut endpoint = Self::get("search/results").param("id", id);
+        if let Some(x) = limit {
+            endpoint = endpoint.param("limit", x);
+        }
+        if let Some(x) = offset {
+            endpoint = endpoint.param("offset", x);
+        }
+        endpoint
+    }
+
+    pub fn search_delete(id: i64) -> Self {
+        Self::post("search/delete").param("id", id)
+    }
+
+    pub fn search_plugins() -> Self {
+        Self::get("search/plugins")
+    }
+
+    pub fn search_install_plugins(sources: &[&str]) -> Self {
+        Self::post("search/installPlugin").param("sources", sources.join("|"))
+    }
+
+    pub fn search_uninstall_plugins(names: &[&str]) -> Self {
+        Self::post("search/uninstallPlugin").param("names", names.join("|"))
+    }
+
+    pub fn search_enable_plugins(names: &[&str], enable: bool) -> Self {
+        Self::post("search/enablePlugin")
+            .param("names", names.join("|"))
+            .param("enable", enable)
+    }
+
+    pub fn search_update_plugins() -> Self {
+        Self::post("search/updatePlugins")
+    }
+
+    /* Torrent creator */
+
+    pub fn creator_add_task(request: &CreateTorrentRequest) -> Self {
+        let mut endpoint = Self::post("torrentcreator/addTask");
+        endpoint.params = request.form();
+        endpoint
+    }
+
+    /// Status of the task `task_id`, or of every task if `task_id` is `None`.
+    pub fn creator_status(task_id: Option<&str>) -> Self {
+        let endpoint = Self::get("torrentcreator/status");
+        match task_id {
+            Some(x) => endpoint.param("taskID", x),
+            None => endpoint,
+        }
+    }
+
+    pub fn creator_torrent_file(task_id: &str) -> Self {
+        Self::get("torrentcreator/torrentFile").param("taskID", task_id)
+    }
+
+    pub fn creator_delete_task(task_id: &str) -> Self {
+        Self::post("torrentcreator/deleteTask").param("taskID", task_id)
+    }
+}
+
+const TORRENT_NOT_FOUND: (StatusCode, &str) = (StatusCode::NOT_FOUND, "Torrent hash was not found");
+const SEARCH_NOT_FOUND: (StatusCode, &str) = (StatusCode::NOT_FOUND, "Search job was not found");
+const TASK_NOT_FOUND: (StatusCode, &str) = (StatusCode::NOT_FOUND, "Task was not found");
+const NO_QUEUEING: (StatusCode, &str) = (StatusCode::CONFLICT, "Torrent queueing is not enabled");
+
+/// Error statuses the Web API documents for the endpoint at `path`. Any other
+/// error status is reported as is.
+fn documented_errors(path: &str) -> StatusTable {
+    match path {
+        "app/setCookies" => &[(
+            StatusCode::BAD_REQUEST,
+            "Request was not a valid json array of cookie objects",
+        )],
+        "torrents/properties"
+        | "torrents/trackers"
+        | "torrents/webseeds"
+        | "torrents/pieceStates"
+        | "torrents/pieceHashes"
+        | "torrents/addTrackers"
+        | "torrents/addWebSeeds"
+        | "torrents/removeWebSeeds"
+        | "sync/torrentPeers" => &[TORRENT_NOT_FOUND],
+        "torrents/files" => &[
+            TORRENT_NOT_FOUND,
+            (
+                StatusCode::CONFLICT,
+                "Torrent metadata was not downloaded yet",
+            ),
+        ],
+        "torrents/add" => &[(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Torrent file is not valid",
+        )],
+        "torrents/setLocation" | "torrents/setSavePath" | "torrents/setDownloadPath" => &[
+            (StatusCode::BAD_REQUEST, "Save path is empty"),
+            (
+                StatusCode::FORBIDDEN,
+                "User does not have write access to directory",
+            ),
+            (StatusCode::CONFLICT, "Unable to create save path directory"),
+        ],
+        "torrents/setCategory" => &[(StatusCode::CONFLICT, "Category name does not exist")],
+        "torrents/createCategory" => &[
+            (StatusCode::BAD_REQUEST, "Category name is empty"),
+            (StatusCode::CONFLICT, "Category name is invalid"),
+        ],
+        "torrents/editCategory" => &[
+            (StatusCode::BAD_REQUEST, "Category name is empty"),
+            (StatusCode::CONFLICT, "Category editing failed"),
+        ],
+        "torrents/increasePrio"
+        | "torrents/decreasePrio"
+        | "torrents/topPrio"
+        | "torrents/bottomPrio" => &[NO_QUEUEING],
+        "torrents/rename" => &[
+            TORRENT_NOT_FOUND,
+            (StatusCode::CONFLICT, "Torrent name is empty"),
+        ],
+        "torrents/renameFile" | "torrents/renameFolder" => &[
+            (StatusCode::BAD_REQUEST, "Missing newPath parameter"),
+            TORRENT_NOT_FOUND,
+            (
+                StatusCode::CONFLICT,
+                "Invalid newPath or oldPath, or newPath already in use",
+            ),
+        ],
+        "torrents/filePrio" => &[
+            (
+                StatusCode::BAD_REQUEST,
+                "Priority is invalid or a file id is not an integer",
+            ),
+            TORRENT_NOT_FOUND,
+            (
+                StatusCode::CONFLICT,
+                "Torrent metadata was not downloaded yet or a file id was not found",
+            ),
+        ],
+        "torrents/editTracker" | "torrents/editWebSeed" => &[
+            (StatusCode::BAD_REQUEST, "newUrl is not a valid URL"),
+            TORRENT_NOT_FOUND,
+            (
+                StatusCode::CONFLICT,
+                "newUrl already exists for the torrent or origUrl was not found",
+            ),
+        ],
+        "torrents/removeTrackers" => &[
+            TORRENT_NOT_FOUND,
+            (StatusCode::CONFLICT, "All urls were not found"),
+        ],
+        "rss/addFolder" => &[(StatusCode::CONFLICT, "Failure to add folder")],
+        "rss/addFeed" => &[(StatusCode::CONFLICT, "Failure to add feed")],
+        "rss/removeItem" => &[(StatusCode::CONFLICT, "Failure to remove item")],
+        "rss/moveItem" => &[(StatusCode::CONFLICT, "Failure to move item")],
+        "search/start" => &[(
+            StatusCode::CONFLICT,
+            "Too many searches are running already",
+        )],
+        "search/stop" | "search/status" | "search/delete" => &[SEARCH_NOT_FOUND],
+        "search/results" => &[
+            SEARCH_NOT_FOUND,
+            (StatusCode::CONFLICT, "Offset is too large or too small"),
+        ],
+        "torrentcreator/status" | "torrentcreator/deleteTask" => &[TASK_NOT_FOUND],
+        "torrentcreator/torrentFile" => &[
+            TASK_NOT_FOUND,
+            (StatusCode::CONFLICT, "Torrent creation is not finished"),
+        ],
+        _ => &[],
+    }
+}
+
+/// Web API version of a server, fetched on first use and cached for the
+/// lifetime of a client.
+#[derive(Debug, Default)]
+pub(super) struct ApiVersion(OnceLock<(u32, u32, u32)>);
+
+impl ApiVersion {
+    pub(super) fn get(&self) -> Option<(u32, u32, u32)> {
+        self.0.get().copied()
+    }
+
+    /// Cache the answer of `app/webapiVersion` and return it, parsed.
+    pub(super) fn set(&self, text: &str) -> Result<(u32, u32, u32)> {
+        let parsed = parse_api_version(text)?;
+        Ok(*self.0.get_or_init(|| parsed))
+    }
+}
+
+/// Parse a Web API version, e.g. "2.11.2", into `(major, minor, patch)`.
+pub(super) fn parse_api_version(text: &str) -> Result<(u32, u32, u32)> {
+    let mut parts = text.trim().split('.').map(|x| x.parse::<u32>());
+    let mut next = || -> Result<u32> {
+        let part = parts.next().unwrap_or(Ok(0));
+        part.map_err(|_| Error::InvalidResponse(format!("Invalid API version: {}", text)))
+    };
+    Ok((next()?, next()?, next()?))
+}
+
+/// Endpoint names of qBittorrent 5 (Web API 2.11.0), which renamed
+/// pause/resume to stop/start and paused to stopped.
+pub(super) const STOP_START_API_VERSION: (u32, u32, u32) = (2, 11, 0);
+
+/// Path of the stop action, "torrents/pause" before Web API 2.11.0.
+pub(super) fn stop_path(stop_start: bool) -> &'static str {
+    if stop_start {
+        "torrents/stop"
+    } else {
+        "torrents/pause"
+    }
+}
+
+/// Path of the start action, "torrents/resume" before Web API 2.11.0.
+pub(super) fn start_path(stop_start: bool) -> &'static str {
+    if stop_start {
+        "torrents/start"
+    } else {
+        "torrents/resume"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(endpoint: &Endpoint) -> Vec<(&str, &str)> {
+        endpoint
+            .params
+            .iter()
+            .map(|(key, value)| (*key, value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn api_version() {
+        assert_eq!(parse_api_version("2.11.4\n").unwrap(), (2, 11, 4));
+        assert_eq!(parse_api_version("2.8").unwrap(), (2, 8, 0));
+        assert!(parse_api_version("v2").is_err());
+
+        let version = ApiVersion::default();
+        assert_eq!(version.get(), None);
+        assert_eq!(version.set("2.11.0").unwrap(), (2, 11, 0));
+        // The first answer is kept.
+        assert_eq!(version.set("2.9.3").unwrap(), (2, 11, 0));
+        assert_eq!(version.get(), Some((2, 11, 0)));
+    }
+
+    #[test]
+    fn stop_start_paths() {
+        assert_eq!(stop_path(true), "torrents/stop");
+        assert_eq!(stop_path(false), "torrents/pause");
+        assert_eq!(start_path(true), "torrents/start");
+        assert_eq!(start_path(false), "torrents/resume");
+    }
+
+    #[test]
+    fn optional_params() {
+        let endpoint = Endpoint::rss_mark_as_read("Linux\\Feed", None);
+        assert_eq!(endpoint.method, Method::Post);
+        assert_eq!(params(&endpoint), [("itemPath", "Linux\\Feed")]);
+
+        let endpoint = Endpoint::search_results(3, None, Some(50));
+        assert_eq!(endpoint.method, Method::Get);
+        assert_eq!(params(&endpoint), [("id", "3"), ("offset", "50")]);
+
+        assert!(Endpoint::search_status(None).params.is_empty());
+        assert_eq!(
+            params(&Endpoint::creator_status(Some("abc"))),
+            [("taskID", "abc")]
+        );
+    }
+
+    #[test]
+    fn list_params() {
+        let endpoint = Endpoint::ban_peers(&["1.2.3.4:6881", "[::1]:6881"]);
+        assert_eq!(params(&endpoint), [("peers", "1.2.3.4:6881|[::1]:6881")]);
+
+        let endpoint = Endpoint::remove_categories(&["a", "b"]);
+        assert_eq!(params(&endpoint), [("categories", "a\nb")]);
+
+        let endpoint = Endpoint::torrent_action("torrents/renameFile", "abc")
+            .params(&[("oldPath", "a"), ("newPath", "b")]);
+        assert_eq!(
+            params(&endpoint),
+            [("hash", "abc"), ("oldPath", "a"), ("newPath", "b")]
+        );
+    }
+
+    #[test]
+    fn download_path_params() {
+        let endpoint = Endpoint::set_download_path("abc|def", "/downloads/incomplete");
+        assert_eq!(endpoint.method, Method::Post);
+        assert_eq!(endpoint.path, "torrents/setDownloadPath");
+        assert_eq!(
+            params(&endpoint),
+            [("id", "abc|def"), ("path", "/downloads/incomplete")]
+        );
+        assert!(!endpoint.errors.is_empty());
+    }
+
+    #[test]
+    fn json_params() {
+        let endpoint = Endpoint::rss_set_rule("rule", &RssRule::default()).unwrap();
+        assert_eq!(endpoint.params[0], ("ruleName", "rule".to_string()));
+        let rule: Value = serde_json::from_str(&endpoint.params[1].1).unwrap();
+        assert_eq!(rule["enabled"], false);
+    }
+}
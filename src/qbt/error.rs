@@ -0,0 +1,227 @@
+use reqwest::StatusCode;
+use std::fmt;
+
+/// Errors returned by the qBittorrent client.
+///
+/// HTTP error statuses are mapped to the meaning the endpoint documents, so
+/// callers can match on them instead of parsing messages.
+#[derive(Debug)]
+pub enum Error {
+    /// 403 on login: the IP is banned for too many failed login attempts
+    Banned,
+    /// Login was refused because of a wrong username or password
+    InvalidCredentials,
+    /// 401, or a bare 403: the session is missing or expired
+    Unauthorized,
+    /// 400: a parameter is missing or invalid
+    BadRequest(String),
+    /// 403 documented by the endpoint, e.g. no write access to a directory
+    Forbidden(String),
+    /// 404: unknown torrent hash, search job, creator task...
+    NotFound(String),
+    /// 409: the request conflicts with the current state, e.g. an invalid
+    /// category name or queueing being disabled
+    Conflict(String),
+    /// 415: the uploaded torrent file is not valid
+    UnsupportedMediaType(String),
+    /// Any HTTP error status the endpoint does not document
+    Status { status: StatusCode, message: String },
+    /// A torrent, peer, category or task is missing from the local state,
+    /// e.g. a sync mirror. No request failed.
+    Unknown(String),
+    /// The server accepted the request but answered that it failed
+    Failed(String),
+    /// The response body is not the JSON we expected
+    Json(serde_json::Error),
+    /// The response is well formed but its content makes no sense
+    InvalidResponse(String),
+    /// The request was rejected before being sent
+    InvalidRequest(String),
+    /// A .torrent file, bencoded value or magnet link is malformed
+    InvalidTorrent(String),
+    /// Connection, TLS, redirect or body read failure
+    Http(reqwest::Error),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error statuses documented by an endpoint, with their meaning.
+pub type StatusTable = &'static [(StatusCode, &'static str)];
+
+/// Body qBittorrent sends with the 403 of a request without a valid session.
+const SESSION_FORBIDDEN: &str = "Forbidden";
+
+impl Error {
+    /// Map an HTTP error status of an endpoint to its meaning in `errors`,
+    /// the endpoint's table. `message` is the response body, which replaces
+    /// the documented meaning unless empty.
+    pub fn from_status(status: StatusCode, message: String, errors: StatusTable) -> Self {
+        if Self::is_session_status(status, &message) {
+            return Self::Unauthorized;
+        }
+        let Some((_, meaning)) = errors.iter().find(|(x, _)| *x == status) else {
+            return Self::Status { status, message };
+        };
+
+        let message = match message.is_empty() {
+            true => meaning.to_string(),
+            false => message,
+        };
+        match status {
+            StatusCode::BAD_REQUEST => Self::BadRequest(message),
+            StatusCode::FORBIDDEN => Self::Forbidden(message),
+            StatusCode::NOT_FOUND => Self::NotFound(message),
+            StatusCode::CONFLICT => Self::Conflict(message),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => Self::UnsupportedMediaType(message),
+            _ => Self::Status { status, message },
+        }
+    }
+
+    /// Whether `status` and `body` mean the session is missing or expired,
+    /// rather than an endpoint refusing the request.
+    pub(super) fn is_session_status(status: StatusCode, body: &str) -> bool {
+        status == StatusCode::UNAUTHORIZED
+            || (status == StatusCode::FORBIDDEN && body.trim() == SESSION_FORBIDDEN)
+    }
+
+    /// HTTP status the server answered with, if that is what failed.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Banned => Some(StatusCode::FORBIDDEN),
+            Self::BadRequest(_) => Some(StatusCode::BAD_REQUEST),
+            Self::Forbidden(_) => Some(StatusCode::FORBIDDEN),
+            Self::NotFound(_) => Some(StatusCode::NOT_FOUND),
+            Self::Conflict(_) => Some(StatusCode::CONFLICT),
+            Self::UnsupportedMediaType(_) => Some(StatusCode::UNSUPPORTED_MEDIA_TYPE),
+            Self::Status { status, .. } => Some(*status),
+            Self::Http(e) => e.status(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let with_message = |f: &mut fmt::Formatter<'_>, what: &str, message: &str| {
+            if message.is_empty() {
+                write!(f, "{}", what)
+            } else {
+                write!(f, "{}: {}", what, message)
+            }
+        };
+
+        match self {
+            Self::Banned => write!(f, "User's IP is banned for too many failed login attempts"),
+            Self::InvalidCredentials => write!(f, "Invalid credentials"),
+            Self::Unauthorized => write!(f, "Not logged in or session expired"),
+            Self::BadRequest(x) => with_message(f, "Bad request", x),
+            Self::Forbidden(x) => with_message(f, "Forbidden", x),
+            Self::NotFound(x) => with_message(f, "Not found", x),
+            Self::Conflict(x) => with_message(f, "Conflict", x),
+            Self::UnsupportedMediaType(x) => with_message(f, "Torrent file is not valid", x),
+            Self::Status { status, message } => with_message(f, &status.to_string(), message),
+            Self::Unknown(x) => write!(f, "{}", x),
+            Self::Failed(x) => write!(f, "{}", x),
+            Self::Json(e) => write!(f, "Invalid JSON response: {}", e),
+            Self::InvalidResponse(x) => write!(f, "Invalid response: {}", x),
+            Self::InvalidRequest(x) => write!(f, "Invalid request: {}", x),
+            Self::InvalidTorrent(x) => write!(f, "Invalid torrent: {}", x),
+            Self::Http(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(e) => Some(e),
+            Self::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => Self::from_status(status, String::new(), &[]),
+            None => Self::Http(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qbt::endpoint::Endpoint;
+
+    fn error(endpoint: &Endpoint, status: StatusCode, body: &str) -> Error {
+        Error::from_status(status, body.to_string(), endpoint.errors)
+    }
+
+    #[test]
+    fn session_statuses() {
+        let endpoint = Endpoint::torrent_list();
+        for (status, body) in [
+            (StatusCode::UNAUTHORIZED, ""),
+            (StatusCode::FORBIDDEN, "Forbidden"),
+        ] {
+            assert!(matches!(
+                error(&endpoint, status, body),
+                Error::Unauthorized
+            ));
+            assert!(Error::is_session_status(status, body));
+        }
+    }
+
+    #[test]
+    fn documented_statuses() {
+        let set_location = Endpoint::torrents_action("torrents/setLocation", "all");
+        match error(&set_location, StatusCode::FORBIDDEN, "") {
+            Error::Forbidden(x) => assert!(x.contains("write access")),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        assert!(!Error::is_session_status(
+            StatusCode::FORBIDDEN,
+            "Cannot write to directory"
+        ));
+
+        let set_category = Endpoint::torrents_action("torrents/setCategory", "all");
+        let e = error(&set_category, StatusCode::CONFLICT, "");
+        assert!(matches!(e, Error::Conflict(_)));
+        assert_eq!(e.status(), Some(StatusCode::CONFLICT));
+
+        let properties = Endpoint::torrent_properties("abc");
+        let e = error(&properties, StatusCode::NOT_FOUND, "Not found");
+        assert!(matches!(e, Error::NotFound(ref x) if x == "Not found"));
+    }
+
+    #[test]
+    fn undocumented_statuses() {
+        // 409 means nothing for the torrent list, 404 means nothing for tags.
+        let e = error(&Endpoint::torrent_list(), StatusCode::CONFLICT, "x");
+        assert!(matches!(
+            e,
+            Error::Status {
+                status: StatusCode::CONFLICT,
+                ..
+            }
+        ));
+        let e = error(&Endpoint::tags(), StatusCode::NOT_FOUND, "");
+        assert_eq!(e.status(), Some(StatusCode::NOT_FOUND));
+        assert!(!matches!(e, Error::NotFound(_)));
+    }
+
+    #[test]
+    fn local_misses() {
+        let e = Error::Unknown("Unknown peer: 1.2.3.4:6881".to_string());
+        assert_eq!(e.status(), None);
+        assert_eq!(e.to_string(), "Unknown peer: 1.2.3.4:6881");
+    }
+}
@@ -1,7 +1,230 @@
-use crate::qbt::core::Client;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
+
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum LogLevel {
+    Normal = 1,
+    Info = 2,
+    Warning = 4,
+    Critical = 8,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 4] = [Self::Normal, Self::Info, Self::Warning, Self::Critical];
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LogEntry {
+    /// ID of the message
+    pub id: i64,
+    /// Text of the message
+    pub message: String,
+    /// Time of the message
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    /// Type of the message
+    #[serde(rename = "type")]
+    pub level: LogLevel,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PeerLogEntry {
+    /// ID of the peer
+    pub id: i64,
+    /// IP of the peer
+    pub ip: String,
+    /// Time of the event
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub timestamp: DateTime<Utc>,
+    /// Whether or not the peer was blocked
+    pub blocked: bool,
+    /// Reason of the block
+    pub reason: String,
+}
+
+/// Entry of a log which can be tailed with `LogTail`.
+pub trait LogId {
+    fn log_id(&self) -> i64;
+}
+
+impl LogId for LogEntry {
+    fn log_id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl LogId for PeerLogEntry {
+    fn log_id(&self) -> i64 {
+        self.id
+    }
+}
+
+/// Fetches the entries of a log after a given id.
+type LogFetch<T> = Box<dyn Fn(&Client, i64) -> Result<Vec<T>>>;
+
+/// Blocking iterator over the new entries of a log, polling the server every
+/// `poll_interval` when there is nothing new.
+pub struct LogTail<T> {
+    client: Client,
+    fetch: LogFetch<T>,
+    last_known_id: i64,
+    poll_interval: Duration,
+    pending: VecDeque<T>,
+}
+
+impl<T: LogId> LogTail<T> {
+    /// Id of the last entry returned, -1 if none.
+    pub fn last_known_id(&self) -> i64 {
+        self.last_known_id
+    }
+}
+
+impl<T: LogId> Iterator for LogTail<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            match (self.fetch)(&self.client, self.last_known_id) {
+                Ok(entries) if entries.is_empty() => thread::sleep(self.poll_interval),
+                Ok(entries) => self.pending.extend(entries),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        let entry = self.pending.pop_front()?;
+        self.last_known_id = entry.log_id();
+        Some(Ok(entry))
+    }
+}
 
 impl Client {
-    // TODO:
-    //   * main
-    //   * peers
+    /// Main log entries of the given `levels` (all of them if empty) with an
+    /// id greater than `last_known_id`, or every entry if `last_known_id` is
+    /// `None`.
+    pub fn get_main_log(
+        &self,
+        levels: &[LogLevel],
+        last_known_id: Option<i64>,
+    ) -> Result<Vec<LogEntry>> {
+        let resp = self.send(&Endpoint::main_log(levels, last_known_id))?;
+        let result: Vec<LogEntry> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Peer log entries with an id greater than `last_known_id`, or every
+    /// entry if `last_known_id` is `None`.
+    pub fn get_peer_log(&self, last_known_id: Option<i64>) -> Result<Vec<PeerLogEntry>> {
+        let resp = self.send(&Endpoint::peer_log(last_known_id))?;
+        let result: Vec<PeerLogEntry> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Follow the main log, starting after `last_known_id`. An empty `levels`
+    /// follows all levels.
+    pub fn tail_main_log(
+        &self,
+        levels: &[LogLevel],
+        last_known_id: Option<i64>,
+        poll_interval: Duration,
+    ) -> LogTail<LogEntry> {
+        let levels = levels.to_vec();
+        LogTail {
+            client: self.clone(),
+            fetch: Box::new(move |client, id| client.get_main_log(&levels, Some(id))),
+            last_known_id: last_known_id.unwrap_or(-1),
+            poll_interval,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Follow the peer log, starting after `last_known_id`.
+    pub fn tail_peer_log(
+        &self,
+        last_known_id: Option<i64>,
+        poll_interval: Duration,
+    ) -> LogTail<PeerLogEntry> {
+        LogTail {
+            client: self.clone(),
+            fetch: Box::new(|client, id| client.get_peer_log(Some(id))),
+            last_known_id: last_known_id.unwrap_or(-1),
+            poll_interval,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qbt::error::Error;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[test]
+    fn timestamps_are_seconds() {
+        let entry: LogEntry = serde_json::from_str(
+            r#"{"id": 3, "message": "Started", "timestamp": 1700000000, "type": 2}"#,
+        )
+        .unwrap();
+        assert_eq!(entry.timestamp.to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert_eq!(entry.level, LogLevel::Info);
+    }
+
+    #[test]
+    fn empty_levels_mean_all_levels() {
+        let endpoint = Endpoint::main_log(&[], None);
+        for key in ["normal", "info", "warning", "critical"] {
+            assert!(endpoint.params.contains(&(key, "true".to_string())));
+        }
+        let endpoint = Endpoint::main_log(&[LogLevel::Warning], Some(4));
+        assert!(endpoint.params.contains(&("info", "false".to_string())));
+        assert!(endpoint
+            .params
+            .contains(&("last_known_id", "4".to_string())));
+    }
+
+    #[test]
+    fn tail_polls_after_last_entry() {
+        let client = Client::new("http://localhost", "", "", true).unwrap();
+        let requested = Rc::new(RefCell::new(vec![]));
+        let log = Rc::clone(&requested);
+        let mut tail = LogTail {
+            client,
+            fetch: Box::new(move |_, id| {
+                log.borrow_mut().push(id);
+                match (id, log.borrow().len()) {
+                    (-1, _) => Ok(vec![1, 2]),
+                    // Nothing new on the first poll
+                    (2, 2) => Ok(vec![]),
+                    (2, _) => Ok(vec![3]),
+                    _ => Err(Error::Unauthorized),
+                }
+            }),
+            last_known_id: -1,
+            poll_interval: Duration::ZERO,
+            pending: VecDeque::new(),
+        };
+
+        let ids: Vec<i64> = tail.by_ref().take(3).map(|x| x.unwrap()).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(tail.last_known_id(), 3);
+        assert!(matches!(tail.next(), Some(Err(Error::Unauthorized))));
+        drop(tail);
+        assert_eq!(*requested.borrow(), vec![-1, 2, 2, 3]);
+    }
+
+    impl LogId for i64 {
+        fn log_id(&self) -> i64 {
+            *self
+        }
+    }
 }
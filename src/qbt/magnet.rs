@@ -0,0 +1,425 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::qbt::error::{Error, Result};
+use crate::qbt::torrent_file::{to_hex, TorrentFile};
+use crate::qbt::torrents::{Torrent, TorrentInfo};
+
+/// Multihash prefix of a SHA-256 digest: function 0x12, length 0x20.
+const SHA256_MULTIHASH: &str = "1220";
+
+/// A magnet link (BEP 9, BEP 52 for v2 torrents, BEP 53 for file selection).
+///
+/// Hashes are kept as the lowercase hex used by the Web API, whatever their
+/// encoding in the link. They are only set by parsing, so they are always
+/// valid.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Magnet {
+    /// SHA-1 infohash, from "xt=urn:btih:" (hex or base32)
+    info_hash_v1: Option<String>,
+    /// SHA-256 infohash, from "xt=urn:btmh:"
+    info_hash_v2: Option<String>,
+    /// Display name ("dn")
+    pub name: Option<String>,
+    /// Trackers ("tr")
+    pub trackers: Vec<String>,
+    /// Web seeds ("ws")
+    pub web_seeds: Vec<String>,
+    /// Total size (bytes) of the torrent ("xl")
+    pub length: Option<u64>,
+    /// Indices of the files to download ("so")
+    pub select_only: Vec<RangeInclusive<u64>>,
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidTorrent(message.to_string())
+}
+
+/// Decode a hex string of `len` bytes to lowercase hex.
+fn normalize_hex(value: &str, len: usize) -> Option<String> {
+    let valid = value.len() == len * 2 && value.bytes().all(|x| x.is_ascii_hexdigit());
+    valid.then(|| value.to_ascii_lowercase())
+}
+
+/// Decode RFC 4648 base32, without padding.
+fn decode_base32(value: &str) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in value.bytes() {
+        let x = match c.to_ascii_uppercase() {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'2'..=b'7' => c - b'2' + 26,
+            _ => return None,
+        };
+        buffer = (buffer << 5) | x as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+fn decode_component(value: &str) -> String {
+    let mut bytes = vec![];
+    let mut iter = value.bytes();
+    while let Some(c) = iter.next() {
+        match c {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex: Vec<u8> = iter.clone().take(2).collect();
+                match std::str::from_utf8(&hex)
+                    .ok()
+                    .filter(|x| x.len() == 2)
+                    .and_then(|x| u8::from_str_radix(x, 16).ok())
+                {
+                    Some(x) => {
+                        bytes.push(x);
+                        iter.nth(1);
+                    }
+                    None => bytes.push(b'%'),
+                }
+            }
+            c => bytes.push(c),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Percent encode everything but unreserved characters and the ones URLs are
+/// readable with.
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|c| match c {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => (c as char).to_string(),
+            b'-' | b'.' | b'_' | b'~' | b':' | b'/' | b'@' => (c as char).to_string(),
+            c => format!("%{:02X}", c),
+        })
+        .collect()
+}
+
+/// Parse "so" file indices, e.g. "0,2,4-6".
+fn parse_select_only(value: &str) -> Result<Vec<RangeInclusive<u64>>> {
+    let index = |x: &str| x.parse::<u64>().map_err(|_| invalid("Invalid file index"));
+    value
+        .split(',')
+        .map(|x| match x.split_once('-') {
+            Some((start, end)) => Ok(index(start)?..=index(end)?),
+            None => index(x).map(|x| x..=x),
+        })
+        .collect()
+}
+
+impl Magnet {
+    pub fn parse(uri: &str) -> Result<Self> {
+        let query = uri
+            .strip_prefix("magnet:?")
+            .ok_or_else(|| invalid("Not a magnet link"))?;
+
+        let mut magnet = Self::default();
+        for pair in query.split('&').filter(|x| !x.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = decode_component(value);
+            // Parameters may be numbered when repeated, e.g. "tr.1".
+            let key = key.split('.').next().unwrap_or(key);
+
+            match key {
+                "xt" => magnet.parse_exact_topic(&value)?,
+                "dn" => magnet.name = Some(value),
+                "tr" => magnet.trackers.push(value),
+                "ws" => magnet.web_seeds.push(value),
+                "xl" => {
+                    let length = value.parse().map_err(|_| invalid("Invalid length"))?;
+                    magnet.length = Some(length);
+                }
+                "so" => magnet.select_only.extend(parse_select_only(&value)?),
+                _ => {}
+            }
+        }
+
+        if magnet.info_hash_v1.is_none() && magnet.info_hash_v2.is_none() {
+            return Err(invalid("Missing BitTorrent infohash"));
+        }
+        Ok(magnet)
+    }
+
+    fn parse_exact_topic(&mut self, value: &str) -> Result<()> {
+        if let Some(hash) = value.strip_prefix("urn:btih:") {
+            let hex = match hash.len() {
+                32 => decode_base32(hash).map(|x| to_hex(&x)),
+                _ => normalize_hex(hash, 20),
+            };
+            self.info_hash_v1 = Some(hex.ok_or_else(|| invalid("Invalid btih hash"))?);
+        } else if let Some(hash) = value.strip_prefix("urn:btmh:") {
+            let hex = hash
+                .strip_prefix(SHA256_MULTIHASH)
+                .and_then(|x| normalize_hex(x, 32))
+                .ok_or_else(|| invalid("Invalid btmh hash"))?;
+            self.info_hash_v2 = Some(hex);
+        }
+        // Other topics (ed2k, sha1...) are not BitTorrent, skip them.
+        Ok(())
+    }
+
+    /// Magnet link of `hash`, a v1 infohash (hex or base32) or a v2 one.
+    pub fn new(hash: &str) -> Result<Self> {
+        let mut magnet = Self::default();
+        match hash.len() {
+            64 => magnet.parse_exact_topic(&format!("urn:btmh:{}{}", SHA256_MULTIHASH, hash))?,
+            _ => magnet.parse_exact_topic(&format!("urn:btih:{}", hash))?,
+        }
+        Ok(magnet)
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn tracker(mut self, url: &str) -> Self {
+        self.trackers.push(url.to_string());
+        self
+    }
+
+    pub fn web_seed(mut self, url: &str) -> Self {
+        self.web_seeds.push(url.to_string());
+        self
+    }
+
+    pub fn length(mut self, length: u64) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Only download the files at `indices`. May be called several times.
+    pub fn select_only(mut self, indices: RangeInclusive<u64>) -> Self {
+        self.select_only.push(indices);
+        self
+    }
+
+    /// SHA-1 infohash (hex)
+    pub fn info_hash_v1(&self) -> Option<&str> {
+        self.info_hash_v1.as_deref()
+    }
+
+    /// SHA-256 infohash (hex)
+    pub fn info_hash_v2(&self) -> Option<&str> {
+        self.info_hash_v2.as_deref()
+    }
+
+    /// Hash qBittorrent identifies the torrent by: the v1 infohash, or the v2
+    /// infohash truncated to 20 bytes for pure v2 torrents.
+    pub fn hash(&self) -> String {
+        match (&self.info_hash_v1, &self.info_hash_v2) {
+            (Some(x), _) => x.clone(),
+            // 64 hex digits, see `parse_exact_topic`
+            (None, Some(x)) => x.get(..40).unwrap_or(x).to_string(),
+            (None, None) => String::new(),
+        }
+    }
+
+    /// Whether `torrent` is the torrent of this link.
+    pub fn matches(&self, torrent: &Torrent) -> bool {
+        self.matches_info(&torrent.info)
+    }
+
+    /// Whether `info` is the torrent of this link. Both infohashes are
+    /// compared, so a link with only the v2 hash of a hybrid torrent matches.
+    pub fn matches_info(&self, info: &TorrentInfo) -> bool {
+        let same = |ours: &Option<String>, theirs: &str| {
+            !theirs.is_empty()
+                && ours
+                    .as_deref()
+                    .is_some_and(|x| x.eq_ignore_ascii_case(theirs))
+        };
+        same(&self.info_hash_v1, &info.infohash_v1)
+            || same(&self.info_hash_v2, &info.infohash_v2)
+            || same(&Some(self.hash()), &info.hash)
+    }
+}
+
+impl FromStr for Magnet {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Magnet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut params: Vec<String> = vec![];
+        if let Some(x) = &self.info_hash_v1 {
+            params.push(format!("xt=urn:btih:{}", x));
+        }
+        if let Some(x) = &self.info_hash_v2 {
+            params.push(format!("xt=urn:btmh:{}{}", SHA256_MULTIHASH, x));
+        }
+        if let Some(x) = &self.name {
+            params.push(format!("dn={}", encode_component(x)));
+        }
+        if let Some(x) = self.length {
+            params.push(format!("xl={}", x));
+        }
+        for x in self.trackers.iter() {
+            params.push(format!("tr={}", encode_component(x)));
+        }
+        for x in self.web_seeds.iter() {
+            params.push(format!("ws={}", encode_component(x)));
+        }
+        if !self.select_only.is_empty() {
+            let ranges: Vec<String> = self
+                .select_only
+                .iter()
+                .map(|x| match x.start() == x.end() {
+                    true => x.start().to_string(),
+                    false => format!("{}-{}", x.start(), x.end()),
+                })
+                .collect();
+            params.push(format!("so={}", ranges.join(",")));
+        }
+        write!(f, "magnet:?{}", params.join("&"))
+    }
+}
+
+impl From<&TorrentFile> for Magnet {
+    fn from(torrent: &TorrentFile) -> Self {
+        Self {
+            info_hash_v1: torrent.info_hash_v1(),
+            info_hash_v2: torrent.info_hash_v2(),
+            name: Some(torrent.name()),
+            trackers: torrent.trackers().into_iter().flatten().collect(),
+            length: Some(torrent.total_size()),
+            ..Default::default()
+        }
+    }
+}
+
+impl TorrentInfo {
+    /// Parsed `magnet_uri`
+    pub fn magnet(&self) -> Result<Magnet> {
+        Magnet::parse(&self.magnet_uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qbt::torrents::tests::torrent_info;
+
+    const V1: &str = "45cc7084ea2069af7603c12707618082666aecfa";
+    const V1_BASE32: &str = "IXGHBBHKEBU265QDYETQOYMAQJTGV3H2";
+    const V2: &str = "b0eed424c81987a16469cc334a0633b00043623ca3b3f509d5921e007bae916c";
+
+    #[test]
+    fn btih_hex_and_base32() {
+        let hex = Magnet::parse(&format!("magnet:?xt=urn:btih:{}", V1.to_uppercase())).unwrap();
+        let base32 = Magnet::parse(&format!("magnet:?xt=urn:btih:{}", V1_BASE32)).unwrap();
+        assert_eq!(hex.info_hash_v1(), Some(V1));
+        assert_eq!(base32.info_hash_v1(), Some(V1));
+        assert_eq!(hex.hash(), V1);
+
+        for hash in [
+            "abc",
+            "45cc7084ea2069af7603c12707618082666aecfz",
+            "IXGHBBHKEBU265QDYETQOYMAQJTGV3H1",
+        ] {
+            assert!(Magnet::parse(&format!("magnet:?xt=urn:btih:{}", hash)).is_err());
+        }
+    }
+
+    #[test]
+    fn btmh() {
+        let magnet = Magnet::parse(&format!("magnet:?xt=urn:btmh:1220{}", V2)).unwrap();
+        assert_eq!(magnet.info_hash_v1(), None);
+        assert_eq!(magnet.info_hash_v2(), Some(V2));
+        // Truncated v2 infohash
+        assert_eq!(magnet.hash(), &V2[..40]);
+
+        // Not SHA-256, or not 32 bytes
+        for topic in [
+            format!("1320{}", V2),
+            format!("1220{}", &V2[..40]),
+            "1220".to_string(),
+        ] {
+            assert!(Magnet::parse(&format!("magnet:?xt=urn:btmh:{}", topic)).is_err());
+        }
+        assert!(Magnet::new("é".repeat(32).as_str()).is_err());
+    }
+
+    #[test]
+    fn parameters() {
+        let uri = format!(
+            "magnet:?xt=urn:btih:{}&dn=Debian+12%20%28netinst%29&xl=1048576\
+             &tr=http%3A%2F%2Fa.example%2Fannounce&tr.1=udp://b.example:6969\
+             &ws=http://seed.example/debian.iso&xt=urn:sha1:ignored&x.pe=1.2.3.4:6881",
+            V1
+        );
+        let magnet = Magnet::parse(&uri).unwrap();
+        assert_eq!(magnet.name.as_deref(), Some("Debian 12 (netinst)"));
+        assert_eq!(magnet.length, Some(1048576));
+        assert_eq!(
+            magnet.trackers,
+            ["http://a.example/announce", "udp://b.example:6969"]
+        );
+        assert_eq!(magnet.web_seeds, ["http://seed.example/debian.iso"]);
+
+        assert!(Magnet::parse("magnet:?dn=x").is_err());
+        assert!(Magnet::parse("http://example.com").is_err());
+        assert_eq!(decode_component("100%"), "100%");
+        assert_eq!(decode_component("%zz%41"), "%zzA");
+    }
+
+    #[test]
+    fn select_only() {
+        let uri = format!("magnet:?xt=urn:btih:{}&so=0,2,4-6", V1);
+        let magnet = Magnet::parse(&uri).unwrap();
+        assert_eq!(magnet.select_only, [0..=0, 2..=2, 4..=6]);
+        assert!(magnet.to_string().ends_with("&so=0,2,4-6"));
+
+        for so in ["", "a", "1-", "1-2-3"] {
+            let uri = format!("magnet:?xt=urn:btih:{}&so={}", V1, so);
+            assert!(Magnet::parse(&uri).is_err(), "{}", so);
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let magnet = Magnet::new(V1)
+            .unwrap()
+            .name("Debian 12 & more")
+            .tracker("http://a.example/announce?key=1")
+            .length(42)
+            .select_only(1..=3);
+        let parsed = Magnet::parse(&magnet.to_string()).unwrap();
+        assert_eq!(parsed, magnet);
+    }
+
+    #[test]
+    fn matches_both_hashes() {
+        let hybrid = TorrentInfo {
+            infohash_v1: V1.to_string(),
+            infohash_v2: V2.to_string(),
+            ..torrent_info(V1)
+        };
+        let btih = Magnet::new(V1).unwrap();
+        let btmh = Magnet::new(V2).unwrap();
+        assert!(btih.matches_info(&hybrid));
+        assert!(btmh.matches_info(&hybrid));
+
+        // Older servers only send `hash`.
+        assert!(btih.matches_info(&torrent_info(V1)));
+        assert!(!btmh.matches_info(&torrent_info(V1)));
+
+        // A pure v2 torrent is identified by its truncated hash.
+        assert!(btmh.matches_info(&torrent_info(&V2[..40])));
+        assert!(!Magnet::new(V1_BASE32)
+            .unwrap()
+            .matches_info(&torrent_info(&V2[..40])));
+    }
+}
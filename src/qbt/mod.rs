@@ -1,7 +1,28 @@
+pub mod add;
 pub mod application;
+pub mod auth;
+pub mod bencode;
+pub mod bulk;
+pub mod categories;
 pub mod core;
+pub mod creator;
+pub mod endpoint;
+pub mod error;
 pub mod log;
-// mod peers;
-// mod sync;
+pub mod magnet;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+pub mod peers;
+pub mod pieces;
+pub mod preferences;
+pub mod query;
+pub mod rss;
+pub mod schedule;
+pub mod search;
+pub mod sync;
+pub mod tags;
+pub mod torrent_file;
 pub mod torrents;
 pub mod transfer;
+
+pub use error::{Error, Result};
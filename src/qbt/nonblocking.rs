@@ -0,0 +1,430 @@
+use reqwest::{
+    self,
+    header::{self, HeaderMap},
+    multipart::{Form, Part},
+    RequestBuilder, Response, StatusCode,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::time::Instant;
+use tokio::sync::Mutex;
+
+use crate::qbt::add::{check_add_response, stopped_field, AddTorrentRequest};
+use crate::qbt::application::BuildInfo;
+use crate::qbt::auth::{check_login_response, Reauth, ReauthEvent, ReauthPolicy, ReauthStep};
+use crate::qbt::bulk::TorrentSelector;
+use crate::qbt::categories::Category;
+use crate::qbt::core::Credentials;
+use crate::qbt::endpoint::{
+    start_path, stop_path, ApiVersion, Endpoint, Method, STOP_START_API_VERSION,
+};
+use crate::qbt::error::{Error, Result, StatusTable};
+use crate::qbt::log::{LogEntry, LogLevel, PeerLogEntry};
+use crate::qbt::peers::{PeerDiff, PeerList};
+use crate::qbt::pieces::PieceMap;
+use crate::qbt::preferences::Preferences;
+use crate::qbt::query::TorrentListQuery;
+use crate::qbt::sync::{MainData, MainDataDiff};
+use crate::qbt::tags::Tags;
+use crate::qbt::torrents::{GenericInfo, Item, TorrentInfo, Tracker, WebSeed};
+use crate::qbt::transfer::{parse_limit, ByteRate, TransferInfo};
+
+/// Async counterpart of `Client`, built on reqwest's async API. It sends the
+/// same `Endpoint`s and returns the same types as the blocking client.
+///
+/// Only the most used calls have a typed method; anything else can go
+/// through `send` with an `Endpoint`. Expired sessions are renewed with the
+/// same `ReauthPolicy` as the blocking client.
+#[derive(Debug)]
+pub struct AsyncClient {
+    base_url: String,
+    credentials: Credentials,
+    session: reqwest::Client,
+    api_version: ApiVersion,
+    reauth: Reauth,
+    /// Held while logging in again, see `Reauth`
+    login: Mutex<()>,
+}
+
+impl AsyncClient {
+    fn url(&self, endpoint: &str) -> String {
+        format!("{}/{}", self.base_url, endpoint)
+    }
+
+    pub fn new(base_url: &str, username: &str, password: &str, ssl_verify: bool) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        let referer = header::HeaderValue::from_str(base_url)
+            .map_err(|_| Error::InvalidRequest(format!("Invalid base URL: {}", base_url)))?;
+        headers.append("Referer", referer);
+
+        let session = reqwest::ClientBuilder::new()
+            .danger_accept_invalid_certs(!ssl_verify)
+            .default_headers(headers)
+            .cookie_store(true)
+            .build()?;
+        let base_url = format!("{}/api/v2", base_url);
+        Ok(Self {
+            base_url,
+            credentials: Credentials::new(username, password),
+            session,
+            api_version: ApiVersion::default(),
+            reauth: Reauth::default(),
+            login: Mutex::new(()),
+        })
+    }
+
+    fn request(&self, endpoint: &Endpoint) -> RequestBuilder {
+        let url = self.url(endpoint.path);
+        match endpoint.method {
+            Method::Get => self.session.get(url).query(&endpoint.params),
+            Method::Post => self.session.post(url).form(&endpoint.params),
+        }
+    }
+
+    /// Send `endpoint`, failing on HTTP error statuses.
+    pub async fn send(&self, endpoint: &Endpoint) -> Result<Response> {
+        self.execute(endpoint, || Ok(self.request(endpoint))).await
+    }
+
+    /// Async counterpart of `Client::execute`.
+    async fn execute<F>(&self, endpoint: &Endpoint, build: F) -> Result<Response>
+    where
+        F: Fn() -> Result<RequestBuilder>,
+    {
+        let sent_at = Instant::now();
+        let resp = build()?.send().await?;
+        match resp.status() {
+            StatusCode::UNAUTHORIZED => {}
+            StatusCode::FORBIDDEN => {
+                let body = resp.text().await.unwrap_or_default();
+                if !Error::is_session_status(StatusCode::FORBIDDEN, &body) {
+                    return Err(Error::from_status(
+                        StatusCode::FORBIDDEN,
+                        body,
+                        endpoint.errors,
+                    ));
+                }
+            }
+            _ => return check_status(resp, endpoint.errors).await,
+        }
+
+        self.renew_session(endpoint.path, sent_at).await?;
+        check_status(build()?.send().await?, endpoint.errors).await
+    }
+
+    /// Async counterpart of `Client::renew_session`.
+    async fn renew_session(&self, path: &str, sent_at: Instant) -> Result<()> {
+        let _login = self.login.lock().await;
+        match self.reauth.begin(path, sent_at)? {
+            ReauthStep::Replay => Ok(()),
+            ReauthStep::Login => self.reauth.finish(path, self.authenticate().await),
+        }
+    }
+
+    async fn json<T: DeserializeOwned>(&self, endpoint: &Endpoint) -> Result<T> {
+        let text = self.send(endpoint).await?.text().await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+
+    async fn text(&self, endpoint: &Endpoint) -> Result<String> {
+        Ok(self.send(endpoint).await?.text().await?)
+    }
+
+    /* Authentication */
+
+    pub async fn login(&self) -> Result<()> {
+        self.authenticate().await?;
+        self.reauth.reset();
+        Ok(())
+    }
+
+    async fn authenticate(&self) -> Result<()> {
+        let endpoint = self.credentials.login_endpoint();
+        let resp = self.request(&endpoint).send().await?;
+        let status = resp.status();
+        check_login_response(status, &resp.text().await?)
+    }
+
+    /// Close the session. Later requests fail as unauthorized instead of
+    /// logging in again, until the next `login`.
+    pub async fn logout(&self) -> Result<()> {
+        self.send(&Endpoint::logout()).await?;
+        self.reauth.disable();
+        Ok(())
+    }
+
+    /// Change the re-login policy of this client.
+    pub fn set_reauth_policy(&self, policy: ReauthPolicy) {
+        self.reauth.set_policy(policy);
+    }
+
+    /// Call `hook` every time a request hits an expired session. It must not
+    /// use the client.
+    pub fn on_reauth<F>(&self, hook: F)
+    where
+        F: Fn(&ReauthEvent) + Send + Sync + 'static,
+    {
+        self.reauth.set_hook(hook);
+    }
+
+    /* Application */
+
+    pub async fn get_version(&self) -> Result<String> {
+        self.text(&Endpoint::version()).await
+    }
+
+    pub async fn get_api_version(&self) -> Result<String> {
+        self.text(&Endpoint::webapi_version()).await
+    }
+
+    /// Check the Web API version against `(major, minor, patch)`, fetching it
+    /// once and caching it for the lifetime of the client.
+    pub async fn api_version_at_least(&self, version: (u32, u32, u32)) -> Result<bool> {
+        let current = match self.api_version.get() {
+            Some(x) => x,
+            None => self.api_version.set(&self.get_api_version().await?)?,
+        };
+        Ok(current >= version)
+    }
+
+    pub async fn get_build_info(&self) -> Result<BuildInfo> {
+        self.json(&Endpoint::build_info()).await
+    }
+
+    pub async fn get_default_save_path(&self) -> Result<String> {
+        self.text(&Endpoint::default_save_path()).await
+    }
+
+    pub async fn get_preferences(&self) -> Result<Preferences> {
+        self.json(&Endpoint::preferences()).await
+    }
+
+    /// Send only the fields of `prefs` which differ from `base`.
+    pub async fn update_preferences(&self, base: &Preferences, prefs: &Preferences) -> Result<()> {
+        let changes = prefs.changes_from(base)?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.send(&Endpoint::set_preferences(&changes)?).await?;
+        Ok(())
+    }
+
+    /* Transfer */
+
+    pub async fn get_global_transfer_info(&self) -> Result<TransferInfo> {
+        self.json(&Endpoint::transfer_info()).await
+    }
+
+    pub async fn alternative_speed_limits_enabled(&self) -> Result<bool> {
+        Ok(self.text(&Endpoint::speed_limits_mode()).await? == "1")
+    }
+
+    pub async fn toggle_alternative_speed_limits(&self) -> Result<()> {
+        self.send(&Endpoint::toggle_speed_limits_mode()).await?;
+        Ok(())
+    }
+
+    pub async fn get_global_download_limit(&self) -> Result<ByteRate> {
+        parse_limit(&self.text(&Endpoint::download_limit()).await?)
+    }
+
+    pub async fn set_global_download_limit(&self, limit: ByteRate) -> Result<()> {
+        self.send(&Endpoint::set_download_limit(limit)).await?;
+        Ok(())
+    }
+
+    pub async fn get_global_upload_limit(&self) -> Result<ByteRate> {
+        parse_limit(&self.text(&Endpoint::upload_limit()).await?)
+    }
+
+    pub async fn set_global_upload_limit(&self, limit: ByteRate) -> Result<()> {
+        self.send(&Endpoint::set_upload_limit(limit)).await?;
+        Ok(())
+    }
+
+    /* Torrents */
+
+    pub async fn get_torrent_list(&self) -> Result<Vec<TorrentInfo>> {
+        self.json(&Endpoint::torrent_list()).await
+    }
+
+    /// Torrents matching `query`, filtered and sorted by the server. Use its
+    /// offset and limit to fetch a large list in pages.
+    pub async fn query_torrents(&self, query: &TorrentListQuery) -> Result<Vec<TorrentInfo>> {
+        let stop_start = match query.needs_api_version() {
+            true => self.api_version_at_least(STOP_START_API_VERSION).await?,
+            false => true,
+        };
+        self.json(&Endpoint::torrent_query(query, stop_start)).await
+    }
+
+    pub async fn get_generic_properties(&self, hash: &str) -> Result<GenericInfo> {
+        self.json(&Endpoint::torrent_properties(hash)).await
+    }
+
+    pub async fn get_trackers(&self, hash: &str) -> Result<Vec<Tracker>> {
+        self.json(&Endpoint::torrent_trackers(hash)).await
+    }
+
+    pub async fn get_webseeds(&self, hash: &str) -> Result<Vec<WebSeed>> {
+        self.json(&Endpoint::torrent_webseeds(hash)).await
+    }
+
+    /// Files of a torrent. The items are not attached to a `Torrent`.
+    pub async fn get_contents(&self, hash: &str) -> Result<Vec<Item>> {
+        self.json(&Endpoint::torrent_files(hash, None)).await
+    }
+
+    pub async fn get_piece_states(&self, hash: &str) -> Result<PieceMap> {
+        let states: Vec<u8> = self.json(&Endpoint::piece_states(hash)).await?;
+        PieceMap::from_raw(&states)
+    }
+
+    pub async fn get_piece_hashes(&self, hash: &str) -> Result<Vec<String>> {
+        self.json(&Endpoint::piece_hashes(hash)).await
+    }
+
+    pub async fn add_torrents(&self, request: &AddTorrentRequest) -> Result<()> {
+        let stopped = stopped_field(self.api_version_at_least(STOP_START_API_VERSION).await?);
+
+        let fields = request.fields(stopped)?;
+        let endpoint = Endpoint::add_torrents();
+
+        // Multipart bodies can't be cloned, so the form is rebuilt if the
+        // request has to be replayed after logging in again.
+        let build = || -> Result<RequestBuilder> {
+            let mut form = Form::new();
+            for (key, value) in fields.iter() {
+                form = form.text(*key, value.clone());
+            }
+            for (filename, contents) in request.torrent_files() {
+                let part = Part::bytes(contents.clone())
+                    .file_name(filename.clone())
+                    .mime_str("application/x-bittorrent")?;
+                form = form.part("torrents", part);
+            }
+            Ok(self.session.post(self.url(endpoint.path)).multipart(form))
+        };
+
+        let resp = self.execute(&endpoint, build).await?;
+        check_add_response(&resp.text().await?)
+    }
+
+    /// POST `endpoint` for every torrent of `selector` in a single request,
+    /// with `params` as extra form fields.
+    pub async fn torrents_action(
+        &self,
+        endpoint: &'static str,
+        selector: &TorrentSelector,
+        params: &[(&'static str, &str)],
+    ) -> Result<()> {
+        let torrents = match selector.needs_torrent_list() {
+            true => self.get_torrent_list().await?,
+            false => vec![],
+        };
+        let Some(hashes) = selector.hashes(&torrents) else {
+            return Ok(());
+        };
+
+        let endpoint = Endpoint::torrents_action(endpoint, &hashes).params(params);
+        self.send(&endpoint).await?;
+        Ok(())
+    }
+
+    pub async fn pause_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        let stop_start = self.api_version_at_least(STOP_START_API_VERSION).await?;
+        self.torrents_action(stop_path(stop_start), selector, &[])
+            .await
+    }
+
+    pub async fn resume_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        let stop_start = self.api_version_at_least(STOP_START_API_VERSION).await?;
+        self.torrents_action(start_path(stop_start), selector, &[])
+            .await
+    }
+
+    /// Remove the torrents, and their downloaded data if `delete_files` is set.
+    pub async fn delete_torrents(
+        &self,
+        selector: &TorrentSelector,
+        delete_files: bool,
+    ) -> Result<()> {
+        let delete_files = delete_files.to_string();
+        let params = [("deleteFiles", delete_files.as_str())];
+        self.torrents_action("torrents/delete", selector, &params)
+            .await
+    }
+
+    pub async fn recheck_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        self.torrents_action("torrents/recheck", selector, &[])
+            .await
+    }
+
+    pub async fn reannounce_torrents(&self, selector: &TorrentSelector) -> Result<()> {
+        self.torrents_action("torrents/reannounce", selector, &[])
+            .await
+    }
+
+    pub async fn get_categories(&self) -> Result<HashMap<String, Category>> {
+        self.json(&Endpoint::categories()).await
+    }
+
+    pub async fn get_tags(&self) -> Result<Tags> {
+        let tags: Vec<String> = self.json(&Endpoint::tags()).await?;
+        Ok(tags.into_iter().collect())
+    }
+
+    /* Sync */
+
+    /// Fetch the changes since `state.rid` and merge them into `state`.
+    pub async fn sync_maindata(&self, state: &mut MainData) -> Result<MainDataDiff> {
+        let resp = self.json(&Endpoint::maindata(state.rid)).await?;
+        Ok(state.apply(resp))
+    }
+
+    /// Fetch the peer changes of a torrent since `state.rid` and merge them
+    /// into `state`.
+    pub async fn get_peers(&self, hash: &str, state: &mut PeerList) -> Result<PeerDiff> {
+        let resp = self.json(&Endpoint::torrent_peers(hash, state.rid)).await?;
+        Ok(state.apply(resp))
+    }
+
+    /* Log */
+
+    pub async fn get_main_log(
+        &self,
+        levels: &[LogLevel],
+        last_known_id: Option<i64>,
+    ) -> Result<Vec<LogEntry>> {
+        self.json(&Endpoint::main_log(levels, last_known_id)).await
+    }
+
+    pub async fn get_peer_log(&self, last_known_id: Option<i64>) -> Result<Vec<PeerLogEntry>> {
+        self.json(&Endpoint::peer_log(last_known_id)).await
+    }
+}
+
+/// Async counterpart of `core::check_status`.
+async fn check_status(resp: Response, errors: StatusTable) -> Result<Response> {
+    let status = resp.status();
+    if status.is_client_error() || status.is_server_error() {
+        let message = resp.text().await.unwrap_or_default();
+        return Err(Error::from_status(status, message, errors));
+    }
+    Ok(resp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>(_: T) {}
+
+    #[test]
+    fn futures_are_send() {
+        let client = AsyncClient::new("http://localhost", "admin", "", true).unwrap();
+        let endpoint = Endpoint::version();
+        assert_send(client.send(&endpoint));
+        assert_send(client.add_torrents(&AddTorrentRequest::new()));
+    }
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::qbt::core::parse_json;
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::{Error, Result};
+use crate::qbt::sync::merge_fields;
+use crate::qbt::torrents::Torrent;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Peer {
+    /// Peer IP address
+    pub ip: String,
+    /// Peer port
+    pub port: u16,
+    /// Client name reported by the peer
+    pub client: String,
+    /// Client name derived from the peer id
+    pub peer_id_client: String,
+    /// Connection type (BT, uTP, Web)
+    pub connection: String,
+    /// Peer flags, e.g. "D X E P"
+    pub flags: String,
+    /// Human readable description of the flags
+    pub flags_desc: String,
+    /// Peer country name
+    pub country: String,
+    /// Peer country code
+    pub country_code: String,
+    /// Peer progress (percentage/100)
+    pub progress: f32,
+    /// Download speed from this peer (bytes/s)
+    pub dl_speed: i64,
+    /// Upload speed to this peer (bytes/s)
+    pub up_speed: i64,
+    /// Amount of data downloaded from this peer (bytes)
+    pub downloaded: i64,
+    /// Amount of data uploaded to this peer (bytes)
+    pub uploaded: i64,
+    /// How much of the pieces we are missing this peer has (percentage/100)
+    pub relevance: f32,
+    /// Newline-separated list of the files this peer is transferring
+    pub files: String,
+}
+
+impl Peer {
+    /// True if the peer is incomplete and has nothing we need.
+    pub fn is_leecher(&self) -> bool {
+        self.progress < 1.0 && self.relevance == 0.0
+    }
+}
+
+/// Raw response of `sync/torrentPeers`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(super) struct PeersResponse {
+    /// Response ID
+    rid: i64,
+    /// Whether the response contains all the data or partial data
+    #[serde(default)]
+    full_update: bool,
+    /// Property: "ip:port", value: changed peer fields
+    #[serde(default)]
+    peers: HashMap<String, Map<String, Value>>,
+    /// List of "ip:port" of peers removed since last request
+    #[serde(default)]
+    peers_removed: Vec<String>,
+}
+
+/// Local mirror of the peers of a single torrent, kept up to date by
+/// `Torrent::get_peers`.
+#[derive(Debug, Default, Clone)]
+pub struct PeerList {
+    /// Last response id seen, 0 before the first sync
+    pub rid: i64,
+    /// "ip:port" -> merged peer fields
+    pub peers: HashMap<String, Map<String, Value>>,
+}
+
+/// Changes applied to a `PeerList` by a single sync.
+#[derive(Debug, Default, Clone)]
+pub struct PeerDiff {
+    /// True if the server replaced the whole peer list
+    pub full_update: bool,
+    /// "ip:port" of the peers that connected
+    pub added: Vec<String>,
+    /// "ip:port" -> names of the fields that changed
+    pub changed: HashMap<String, Vec<String>>,
+    /// "ip:port" of the peers that disconnected
+    pub removed: Vec<String>,
+}
+
+impl PeerList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Typed view of a single peer of the mirror.
+    pub fn peer(&self, key: &str) -> Result<Peer> {
+        let fields = self
+            .peers
+            .get(key)
+            .ok_or_else(|| Error::Unknown(format!("Unknown peer: {}", key)))?;
+        let peer = serde_json::from_value(Value::Object(fields.clone()))?;
+        Ok(peer)
+    }
+
+    /// Typed view of every peer of the mirror.
+    pub fn peer_list(&self) -> Result<Vec<Peer>> {
+        self.peers.keys().map(|key| self.peer(key)).collect()
+    }
+
+    pub(super) fn apply(&mut self, resp: PeersResponse) -> PeerDiff {
+        let mut diff = PeerDiff {
+            full_update: resp.full_update,
+            ..Default::default()
+        };
+
+        if resp.full_update {
+            diff.removed = self
+                .peers
+                .keys()
+                .filter(|x| !resp.peers.contains_key(*x))
+                .cloned()
+                .collect();
+            for key in diff.removed.iter() {
+                self.peers.remove(key);
+            }
+        } else {
+            for key in resp.peers_removed {
+                if self.peers.remove(&key).is_some() {
+                    diff.removed.push(key);
+                }
+            }
+        }
+
+        for (key, fields) in resp.peers {
+            match self.peers.get_mut(&key) {
+                Some(peer) => {
+                    let changed = merge_fields(peer, fields);
+                    if !changed.is_empty() {
+                        diff.changed.insert(key, changed);
+                    }
+                }
+                None => {
+                    self.peers.insert(key.clone(), fields);
+                    diff.added.push(key);
+                }
+            }
+        }
+
+        self.rid = resp.rid;
+
+        diff
+    }
+}
+
+impl Torrent {
+    /// Fetch the peer changes since `state.rid` and merge them into `state`.
+    pub fn get_peers(&self, state: &mut PeerList) -> Result<PeerDiff> {
+        let resp = self
+            .client
+            .send(&Endpoint::torrent_peers(&self.info.hash, state.rid))?;
+        let result: PeersResponse = parse_json(resp)?;
+        Ok(state.apply(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response(value: Value) -> PeersResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn peer_updates() {
+        let mut list = PeerList::new();
+        let diff = list.apply(response(json!({
+            "rid": 1,
+            "full_update": true,
+            "peers": {
+                "1.2.3.4:6881": { "ip": "1.2.3.4", "port": 6881, "progress": 0.5, "relevance": 0 },
+                "[::1]:51413": { "ip": "::1", "port": 51413, "progress": 1.0 }
+            }
+        })));
+        assert!(diff.full_update);
+        assert_eq!(diff.added.len(), 2);
+        assert!(list.peer("1.2.3.4:6881").unwrap().is_leecher());
+        assert!(!list.peer("[::1]:51413").unwrap().is_leecher());
+
+        let diff = list.apply(response(json!({
+            "rid": 2,
+            "peers": { "1.2.3.4:6881": { "progress": 0.75, "port": 6881 } },
+            "peers_removed": ["[::1]:51413", "5.6.7.8:1"]
+        })));
+        assert_eq!(list.rid, 2);
+        assert_eq!(diff.changed["1.2.3.4:6881"], ["progress"]);
+        assert_eq!(diff.removed, ["[::1]:51413"]);
+        assert_eq!(list.peer_list().unwrap().len(), 1);
+        assert_eq!(list.peer("1.2.3.4:6881").unwrap().progress, 0.75);
+    }
+
+    #[test]
+    fn full_update_drops_missing_peers() {
+        let mut list = PeerList::new();
+        list.apply(response(json!({
+            "rid": 1,
+            "full_update": true,
+            "peers": { "a:1": {}, "b:2": {} }
+        })));
+        let diff = list.apply(response(json!({
+            "rid": 7,
+            "full_update": true,
+            "peers": { "b:2": {} }
+        })));
+        assert_eq!(diff.removed, ["a:1"]);
+        assert!(diff.added.is_empty() && diff.changed.is_empty());
+    }
+
+    #[test]
+    fn unknown_peer() {
+        let list = PeerList::new();
+        let e = list.peer("1.2.3.4:6881").unwrap_err();
+        assert!(matches!(e, Error::Unknown(_)));
+        assert_eq!(e.status(), None);
+    }
+}
@@ -0,0 +1,312 @@
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::ops::Range;
+
+use crate::qbt::error::{Error, Result};
+use crate::qbt::torrents::Item;
+
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PieceState {
+    NotDownloaded = 0,
+    Downloading = 1,
+    Downloaded = 2,
+}
+
+impl PieceState {
+    fn from_bits(bits: u8) -> Self {
+        match bits {
+            1 => Self::Downloading,
+            2 => Self::Downloaded,
+            _ => Self::NotDownloaded,
+        }
+    }
+}
+
+/// State of every piece of a torrent, packed in 2 bits per piece.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PieceMap {
+    len: usize,
+    bits: Vec<u8>,
+}
+
+const PIECES_PER_BYTE: usize = 4;
+
+impl PieceMap {
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            bits: vec![0; len.div_ceil(PIECES_PER_BYTE)],
+        }
+    }
+
+    /// Build a map from the raw values returned by `torrents/pieceStates`.
+    pub fn from_raw(states: &[u8]) -> Result<Self> {
+        let mut map = Self::new(states.len());
+        for (i, state) in states.iter().enumerate() {
+            let state = match state {
+                0 => PieceState::NotDownloaded,
+                1 => PieceState::Downloading,
+                2 => PieceState::Downloaded,
+                x => {
+                    let message = format!("Invalid piece state: {}", x);
+                    return Err(Error::InvalidResponse(message));
+                }
+            };
+            map.set(i, state);
+        }
+        Ok(map)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<PieceState> {
+        if index >= self.len {
+            return None;
+        }
+        let shift = (index % PIECES_PER_BYTE) * 2;
+        let bits = (self.bits[index / PIECES_PER_BYTE] >> shift) & 0b11;
+        Some(PieceState::from_bits(bits))
+    }
+
+    pub fn set(&mut self, index: usize, state: PieceState) {
+        assert!(index < self.len, "Piece index out of range");
+        let shift = (index % PIECES_PER_BYTE) * 2;
+        let byte = &mut self.bits[index / PIECES_PER_BYTE];
+        *byte = (*byte & !(0b11 << shift)) | ((state as u8) << shift);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = PieceState> + '_ {
+        (0..self.len).filter_map(|i| self.get(i))
+    }
+
+    /// Number of pieces in `state`.
+    pub fn count(&self, state: PieceState) -> usize {
+        self.iter().filter(|x| *x == state).count()
+    }
+
+    /// True if every piece of `pieces` is downloaded. Out of range pieces are
+    /// never downloaded.
+    pub fn is_downloaded(&self, pieces: Range<usize>) -> bool {
+        pieces.end <= self.len
+            && pieces
+                .into_iter()
+                .all(|i| self.get(i) == Some(PieceState::Downloaded))
+    }
+
+    /// True if the `len` bytes starting at `offset` (relative to the start of
+    /// the torrent) are downloaded.
+    pub fn is_bytes_downloaded(&self, piece_size: u64, offset: u64, len: u64) -> bool {
+        self.is_downloaded(pieces_for_bytes(piece_size, offset, len))
+    }
+
+    /// Consecutive pieces sharing the same state, as `(state, pieces)`.
+    pub fn runs(&self) -> Runs<'_> {
+        Runs { map: self, next: 0 }
+    }
+
+    /// Sub-map of the pieces in `pieces`.
+    pub fn slice(&self, pieces: Range<usize>) -> PieceMap {
+        let end = pieces.end.min(self.len);
+        let start = pieces.start.min(end);
+        let mut map = PieceMap::new(end - start);
+        for i in start..end {
+            if let Some(state) = self.get(i) {
+                map.set(i - start, state);
+            }
+        }
+        map
+    }
+}
+
+/// Pieces covering the `len` bytes starting at `offset`.
+pub fn pieces_for_bytes(piece_size: u64, offset: u64, len: u64) -> Range<usize> {
+    if len == 0 || piece_size == 0 {
+        return 0..0;
+    }
+    let first = offset / piece_size;
+    let last = (offset + len - 1) / piece_size;
+    first as usize..last as usize + 1
+}
+
+pub struct Runs<'a> {
+    map: &'a PieceMap,
+    next: usize,
+}
+
+impl<'a> Iterator for Runs<'a> {
+    type Item = (PieceState, Range<usize>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next;
+        let state = self.map.get(start)?;
+        let mut end = start + 1;
+        while self.map.get(end) == Some(state) {
+            end += 1;
+        }
+        self.next = end;
+        Some((state, start..end))
+    }
+}
+
+impl Item {
+    /// Pieces of the torrent this file spans.
+    pub fn pieces(&self) -> Range<usize> {
+        let (start, end) = self.piece_range;
+        start.max(0) as usize..(end + 1).max(0) as usize
+    }
+
+    /// Completion bitmap of this file, out of the map of its torrent.
+    pub fn piece_map(&self, torrent_pieces: &PieceMap) -> PieceMap {
+        torrent_pieces.slice(self.pieces())
+    }
+
+    /// Offset (bytes) of this file from the start of the torrent, given the
+    /// full file list of the torrent and its piece size.
+    ///
+    /// qBittorrent does not list padding files, so the offset is the end of
+    /// the previous file, pushed to the first piece of this file when padding
+    /// aligns it to a piece boundary, as in v2 and hybrid torrents.
+    pub fn offset(&self, items: &[Item], piece_size: u64) -> u64 {
+        let mut previous: Vec<&Item> = items.iter().filter(|x| x.index < self.index).collect();
+        previous.sort_by_key(|x| x.index);
+
+        let start = |item: &Item, end: u64| {
+            if item.size <= 0 {
+                return end;
+            }
+            end.max(item.piece_range.0.max(0) as u64 * piece_size)
+        };
+        let end = previous
+            .into_iter()
+            .fold(0, |end, x| start(x, end) + x.size.max(0) as u64);
+        start(self, end)
+    }
+
+    /// True if the `len` bytes starting at `offset` (relative to the start of
+    /// this file) are downloaded. `items` is the full file list of the
+    /// torrent. Fails if the range goes past the end of the file.
+    pub fn is_bytes_downloaded(
+        &self,
+        items: &[Item],
+        torrent_pieces: &PieceMap,
+        piece_size: u64,
+        offset: u64,
+        len: u64,
+    ) -> Result<bool> {
+        let size = self.size.max(0) as u64;
+        if offset.checked_add(len).is_none_or(|end| end > size) {
+            let message = format!("Range past the end of file {}", self.name);
+            return Err(Error::InvalidRequest(message));
+        }
+        let file_offset = self.offset(items, piece_size);
+        Ok(torrent_pieces.is_bytes_downloaded(piece_size, file_offset + offset, len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(index: i64, size: i64, piece_range: (i64, i64)) -> Item {
+        serde_json::from_value(serde_json::json!({
+            "index": index,
+            "name": format!("file{}", index),
+            "size": size,
+            "progress": 0.0,
+            "priority": 1,
+            "piece_range": [piece_range.0, piece_range.1],
+            "availability": 0.0,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn packs_states() {
+        let raw = [2, 1, 0, 2, 2, 1, 0];
+        let map = PieceMap::from_raw(&raw).unwrap();
+        assert_eq!(map.len(), 7);
+        assert_eq!(map.bits.len(), 2);
+        let states: Vec<u8> = map.iter().map(|x| x as u8).collect();
+        assert_eq!(states, raw);
+        assert_eq!(map.get(7), None);
+        assert_eq!(map.count(PieceState::Downloaded), 3);
+    }
+
+    #[test]
+    fn set_overwrites_only_its_piece() {
+        let mut map = PieceMap::new(5);
+        map.set(1, PieceState::Downloaded);
+        map.set(2, PieceState::Downloading);
+        map.set(1, PieceState::NotDownloaded);
+        assert_eq!(map.get(1), Some(PieceState::NotDownloaded));
+        assert_eq!(map.get(2), Some(PieceState::Downloading));
+        assert_eq!(map.count(PieceState::NotDownloaded), 4);
+    }
+
+    #[test]
+    fn rejects_unknown_state() {
+        assert!(matches!(
+            PieceMap::from_raw(&[0, 3]),
+            Err(Error::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn runs_and_slices() {
+        let map = PieceMap::from_raw(&[2, 2, 0, 1, 1, 1]).unwrap();
+        let runs: Vec<_> = map.runs().collect();
+        assert_eq!(
+            runs,
+            vec![
+                (PieceState::Downloaded, 0..2),
+                (PieceState::NotDownloaded, 2..3),
+                (PieceState::Downloading, 3..6),
+            ]
+        );
+        assert_eq!(map.slice(3..10), PieceMap::from_raw(&[1, 1, 1]).unwrap());
+        assert!(map.is_downloaded(0..2));
+        assert!(!map.is_downloaded(1..3));
+        assert!(!map.is_downloaded(5..7));
+    }
+
+    #[test]
+    fn byte_ranges_to_pieces() {
+        assert_eq!(pieces_for_bytes(16, 0, 16), 0..1);
+        assert_eq!(pieces_for_bytes(16, 15, 2), 0..2);
+        assert_eq!(pieces_for_bytes(16, 32, 1), 2..3);
+        assert_eq!(pieces_for_bytes(16, 5, 0), 0..0);
+    }
+
+    #[test]
+    fn offsets_without_padding() {
+        // v1: files follow each other inside shared pieces.
+        let items = [item(0, 10, (0, 0)), item(1, 20, (0, 1)), item(2, 5, (1, 2))];
+        assert_eq!(items[0].offset(&items, 16), 0);
+        assert_eq!(items[1].offset(&items, 16), 10);
+        assert_eq!(items[2].offset(&items, 16), 30);
+    }
+
+    #[test]
+    fn offsets_with_padding() {
+        // v2 and hybrid: every file starts on a piece boundary.
+        let items = [item(0, 10, (0, 0)), item(1, 20, (1, 2)), item(2, 5, (3, 3))];
+        assert_eq!(items[1].offset(&items, 16), 16);
+        assert_eq!(items[2].offset(&items, 16), 48);
+    }
+
+    #[test]
+    fn file_byte_ranges() {
+        let items = [item(0, 10, (0, 0)), item(1, 20, (1, 2))];
+        let map = PieceMap::from_raw(&[0, 2, 0]).unwrap();
+        let file = &items[1];
+        assert!(file.is_bytes_downloaded(&items, &map, 16, 0, 16).unwrap());
+        assert!(!file.is_bytes_downloaded(&items, &map, 16, 10, 10).unwrap());
+        assert!(file.is_bytes_downloaded(&items, &map, 16, 10, 11).is_err());
+    }
+}
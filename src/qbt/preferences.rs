@@ -0,0 +1,756 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
+
+/// Application preferences. Every field is optional so that the same struct
+/// can describe both the full preferences returned by the server and a
+/// partial update. Keys unknown to this crate are kept in `extra`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Preferences {
+    /* Downloads */
+    /// Currently selected language (e.g. en_GB for English)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// True if a subfolder should be created when adding a torrent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub create_subfolder_enabled: Option<bool>,
+    /// True if torrents should be added in a paused state
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_paused_enabled: Option<bool>,
+    /// Delete .torrent files afterwards: 0 never, 1 if added, 2 always
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_delete_mode: Option<i64>,
+    /// True if disk space should be pre-allocated for all files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preallocate_all: Option<bool>,
+    /// True if ".!qB" should be appended to incomplete files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incomplete_files_ext: Option<bool>,
+    /// True if Automatic Torrent Management is enabled by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_tmm_enabled: Option<bool>,
+    /// True if torrent should be relocated when its Category changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub torrent_changed_tmm_enabled: Option<bool>,
+    /// True if torrent should be relocated when the default save path changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_path_changed_tmm_enabled: Option<bool>,
+    /// True if torrent should be relocated when its Category's save path changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_changed_tmm_enabled: Option<bool>,
+    /// True if subcategories are enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_subcategories: Option<bool>,
+    /// True if category paths are used for manually managed torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_category_paths_in_manual_mode: Option<bool>,
+    /// Default save path for torrents, separated by slashes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_path: Option<String>,
+    /// True if folder for incomplete torrents is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_path_enabled: Option<bool>,
+    /// Path for incomplete torrents, separated by slashes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_path: Option<String>,
+    /// True if unwanted files are moved to a hidden .unwanted folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_unwanted_folder: Option<bool>,
+    /// Watched folder -> download location (0: watched folder, 1: default save path, path: custom)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scan_dirs: Option<Map<String, Value>>,
+    /// Path to directory to copy .torrent files to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_dir: Option<String>,
+    /// Path to directory to copy .torrent files of completed downloads to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_dir_fin: Option<String>,
+    /// True if files matching `excluded_file_names` are not downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_file_names_enabled: Option<bool>,
+    /// Newline-separated list of file name patterns to exclude
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub excluded_file_names: Option<String>,
+    /// Default content layout (Original, Subfolder, NoSubfolder)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub torrent_content_layout: Option<String>,
+    /// Default stop condition (None, MetadataReceived, FilesChecked)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub torrent_stop_condition: Option<String>,
+    /// True if new torrents are added to the top of the queue
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_to_top_of_queue: Option<bool>,
+    /// True if trackers are merged when adding an existing torrent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_trackers: Option<bool>,
+    /// True if content files are deleted by default when removing torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_torrent_content_files: Option<bool>,
+    /// True if a confirmation is asked before rechecking torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_torrent_recheck: Option<bool>,
+
+    /* Email notification upon download completion */
+    /// True if e-mail notification should be enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_enabled: Option<bool>,
+    /// e-mail where notifications should originate from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_sender: Option<String>,
+    /// e-mail to send notifications to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_email: Option<String>,
+    /// smtp server for e-mail notifications
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_smtp: Option<String>,
+    /// True if smtp server requires SSL connection
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_ssl_enabled: Option<bool>,
+    /// True if smtp server requires authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_auth_enabled: Option<bool>,
+    /// Username for smtp authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_username: Option<String>,
+    /// Password for smtp authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mail_notification_password: Option<String>,
+
+    /* External programs */
+    /// True if an external program should be run when a torrent is added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autorun_on_torrent_added_enabled: Option<bool>,
+    /// Program path/name/arguments to run when a torrent is added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autorun_on_torrent_added_program: Option<String>,
+    /// True if an external program should be run after torrent has finished downloading
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autorun_enabled: Option<bool>,
+    /// Program path/name/arguments to run if `autorun_enabled` is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autorun_program: Option<String>,
+
+    /* Queueing */
+    /// True if torrent queuing is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queueing_enabled: Option<bool>,
+    /// Maximum number of active simultaneous downloads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_downloads: Option<i64>,
+    /// Maximum number of active simultaneous downloads and uploads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_torrents: Option<i64>,
+    /// Maximum number of active simultaneous uploads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_uploads: Option<i64>,
+    /// Maximum number of torrents checked simultaneously
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_active_checking_torrents: Option<i64>,
+    /// If true torrents w/o any activity (stalled ones) will not be counted towards `max_active_*` limits
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dont_count_slow_torrents: Option<bool>,
+    /// Download rate in KiB/s for a torrent to be considered "slow"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_torrent_dl_rate_threshold: Option<i64>,
+    /// Upload rate in KiB/s for a torrent to be considered "slow"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_torrent_ul_rate_threshold: Option<i64>,
+    /// Seconds a torrent should be inactive before considered "slow"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slow_torrent_inactive_timer: Option<i64>,
+
+    /* Share limits */
+    /// True if share ratio limit is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ratio_enabled: Option<bool>,
+    /// Get the global share ratio limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ratio: Option<f64>,
+    /// Action performed when a torrent reaches the maximum share ratio: 0 pause, 1 remove, 3 remove with files, 2 super seeding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ratio_act: Option<i64>,
+    /// True if seeding time limit is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_seeding_time_enabled: Option<bool>,
+    /// Number of minutes to seed a torrent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_seeding_time: Option<i64>,
+    /// True if inactive seeding time limit is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_inactive_seeding_time_enabled: Option<bool>,
+    /// Number of minutes to seed an inactive torrent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_inactive_seeding_time: Option<i64>,
+
+    /* Connection */
+    /// Port for incoming connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen_port: Option<i64>,
+    /// True if UPnP/NAT-PMP is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upnp: Option<bool>,
+    /// True if the port is randomly selected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub random_port: Option<bool>,
+    /// Maximum global number of simultaneous connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connec: Option<i64>,
+    /// Maximum number of simultaneous connections per torrent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_connec_per_torrent: Option<i64>,
+    /// Maximum number of upload slots
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uploads: Option<i64>,
+    /// Maximum number of upload slots per torrent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uploads_per_torrent: Option<i64>,
+    /// Timeout in seconds for a stopped announce request to trackers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_tracker_timeout: Option<i64>,
+    /// True if the advanced libtorrent option piece_extent_affinity is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_piece_extent_affinity: Option<bool>,
+    /// Bittorrent Protocol to use: 0 TCP and μTP, 1 TCP, 2 μTP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bittorrent_protocol: Option<i64>,
+    /// True if `[du]l_limit` should be applied to uTP connections
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_utp_rate: Option<bool>,
+    /// True if `[du]l_limit` should be applied to estimated TCP overhead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_tcp_overhead: Option<bool>,
+    /// True if `[du]l_limit` should be applied to peers on the LAN
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_lan_peers: Option<bool>,
+
+    /* Speed */
+    /// Global download speed limit in KiB/s; -1 means no limit is applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dl_limit: Option<i64>,
+    /// Global upload speed limit in KiB/s; -1 means no limit is applied
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub up_limit: Option<i64>,
+    /// Alternative global download speed limit in KiB/s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_dl_limit: Option<i64>,
+    /// Alternative global upload speed limit in KiB/s
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alt_up_limit: Option<i64>,
+    /// True if alternative limits should be applied according to schedule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduler_enabled: Option<bool>,
+    /// Scheduler starting hour
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_from_hour: Option<i64>,
+    /// Scheduler starting minute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_from_min: Option<i64>,
+    /// Scheduler ending hour
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_to_hour: Option<i64>,
+    /// Scheduler ending minute
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule_to_min: Option<i64>,
+    /// Scheduler days: 0 every day, 1 weekdays, 2 weekends, 3-9 Monday to Sunday
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scheduler_days: Option<i64>,
+
+    /* Bittorrent */
+    /// True if DHT is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dht: Option<bool>,
+    /// True if PeX is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pex: Option<bool>,
+    /// True if LSD is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lsd: Option<bool>,
+    /// Encryption: 0 prefer, 1 force on, 2 force off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<i64>,
+    /// If true anonymous mode will be enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anonymous_mode: Option<bool>,
+    /// True if `add_trackers` are appended to new torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_trackers_enabled: Option<bool>,
+    /// Newline-separated list of trackers appended to new torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_trackers: Option<String>,
+    /// True if trackers fetched from `add_trackers_url` are appended to new torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_trackers_from_url_enabled: Option<bool>,
+    /// URL of a list of trackers appended to new torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub add_trackers_url: Option<String>,
+
+    /* Proxy */
+    /// Proxy type, an integer on older servers and a name (None, HTTP, SOCKS5, SOCKS4) since qBittorrent 4.6
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_type: Option<Value>,
+    /// Proxy IP address or domain name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_ip: Option<String>,
+    /// Proxy port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_port: Option<i64>,
+    /// True if peer and web seed connections should be proxified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_peer_connections: Option<bool>,
+    /// True proxy requires authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_auth_enabled: Option<bool>,
+    /// Username for proxy authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_username: Option<String>,
+    /// Password for proxy authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_password: Option<String>,
+    /// True if host names are resolved through the proxy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_hostname_lookup: Option<bool>,
+    /// True if the proxy is used for BitTorrent purposes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_bittorrent: Option<bool>,
+    /// True if the proxy is used for RSS purposes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_rss: Option<bool>,
+    /// True if the proxy is used for general purposes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_misc: Option<bool>,
+    /// True if proxy is only used for torrents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_torrents_only: Option<bool>,
+
+    /* IP filtering */
+    /// True if external IP filter should be enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_filter_enabled: Option<bool>,
+    /// Path to IP filter file (.dat, .p2p, .p2b files are supported)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_filter_path: Option<String>,
+    /// True if IP filters are applied to trackers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_filter_trackers: Option<bool>,
+    /// Newline-separated list of manually banned IPs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "banned_IPs")]
+    pub banned_ips: Option<String>,
+
+    /* Web UI */
+    /// Semicolon-separated list of domains to accept when performing Host header validation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_domain_list: Option<String>,
+    /// IP address to use for the WebUI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_address: Option<String>,
+    /// WebUI port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_port: Option<i64>,
+    /// True if UPnP is used for the WebUI port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_upnp: Option<bool>,
+    /// WebUI username
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_username: Option<String>,
+    /// For API ≥ v2.3.0: Plaintext WebUI password, not readable, write-only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_password: Option<String>,
+    /// True if WebUI CSRF protection is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_csrf_protection_enabled: Option<bool>,
+    /// True if WebUI clickjacking protection is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_clickjacking_protection_enabled: Option<bool>,
+    /// True if WebUI cookie Secure flag is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_secure_cookie_enabled: Option<bool>,
+    /// Maximum number of authentication failures before WebUI access ban
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_max_auth_fail_count: Option<i64>,
+    /// WebUI access ban duration in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_ban_duration: Option<i64>,
+    /// Seconds until WebUI is automatically signed off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_session_timeout: Option<i64>,
+    /// True if WebUI host header validation is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_host_header_validation_enabled: Option<bool>,
+    /// True if authentication challenge for loopback address (127.0.0.1) should be disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bypass_local_auth: Option<bool>,
+    /// True if webui authentication should be bypassed for clients whose ip resides within (at least) one of the subnets on the whitelist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bypass_auth_subnet_whitelist_enabled: Option<bool>,
+    /// (White)list of ipv4/ipv6 subnets for which webui authentication should be bypassed; list entries are separated by commas
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bypass_auth_subnet_whitelist: Option<String>,
+    /// True if an alternative WebUI should be used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternative_webui_enabled: Option<bool>,
+    /// File path to the alternative WebUI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alternative_webui_path: Option<String>,
+    /// True if WebUI HTTPS access is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_https: Option<bool>,
+    /// Path to the SSL keyfile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_https_key_path: Option<String>,
+    /// Path to the SSL certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_https_cert_path: Option<String>,
+    /// True if `web_ui_custom_http_headers` are sent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_use_custom_http_headers_enabled: Option<bool>,
+    /// Newline-separated list of extra HTTP headers sent by the WebUI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_custom_http_headers: Option<String>,
+    /// True if the WebUI trusts reverse proxy headers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_reverse_proxy_enabled: Option<bool>,
+    /// Semicolon-separated list of trusted reverse proxies
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web_ui_reverse_proxies_list: Option<String>,
+    /// True if server DNS should be updated dynamically
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dyndns_enabled: Option<bool>,
+    /// Dynamic DNS service: 0 DyDNS, 1 NOIP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dyndns_service: Option<i64>,
+    /// Username for DDNS service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dyndns_username: Option<String>,
+    /// Password for DDNS service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dyndns_password: Option<String>,
+    /// Your DDNS domain name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dyndns_domain: Option<String>,
+
+    /* RSS */
+    /// RSS refresh interval (minutes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_refresh_interval: Option<i64>,
+    /// Delay between two RSS feed fetches (seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_fetch_delay: Option<i64>,
+    /// Max stored articles per RSS feed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_max_articles_per_feed: Option<i64>,
+    /// Enable processing of RSS feeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_processing_enabled: Option<bool>,
+    /// Enable auto-downloading of torrents from the RSS feeds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_auto_downloading_enabled: Option<bool>,
+    /// For API ≥ v2.5.1: Enable downloading of repack/proper Episodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_download_repack_proper_episodes: Option<bool>,
+    /// For API ≥ v2.5.1: List of RSS Smart Episode Filters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rss_smart_episode_filters: Option<String>,
+
+    /* Application */
+    /// Name of this instance, shown in the WebUI title
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_instance_name: Option<String>,
+    /// WebUI refresh interval (milliseconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval: Option<i64>,
+    /// True if the external IP is shown in the status bar
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_bar_external_ip: Option<bool>,
+    /// True if libtorrent performance warnings are logged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub performance_warning: Option<bool>,
+    /// True if the log is written to a file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_enabled: Option<bool>,
+    /// Directory of the log file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_path: Option<String>,
+    /// True if the log file is rotated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_backup_enabled: Option<bool>,
+    /// Maximum size of the log file (KiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_max_size: Option<i64>,
+    /// True if old log files are deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_delete_old: Option<bool>,
+    /// Age after which old log files are deleted, in `file_log_age_type` units
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_age: Option<i64>,
+    /// Unit of `file_log_age`: 0 days, 1 months, 2 years
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_log_age_type: Option<i64>,
+    /// True if peer countries should be resolved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolve_peer_countries: Option<bool>,
+    /// True if torrents should be rechecked on completion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recheck_completed_torrents: Option<bool>,
+    /// True if internationalized domain names are supported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idn_support_enabled: Option<bool>,
+    /// True if server-side request forgery mitigation is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssrf_mitigation: Option<bool>,
+    /// True if HTTPS tracker certificates are validated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate_https_tracker_certificate: Option<bool>,
+    /// True if peers on privileged ports are blocked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_peers_on_privileged_ports: Option<bool>,
+    /// Physical memory (RAM) usage limit (MiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_working_set_limit: Option<i64>,
+    /// Maximum size of a .torrent file (bytes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub torrent_file_size_limit: Option<i64>,
+    /// Maximum nesting depth when decoding .torrent files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bdecode_depth_limit: Option<i64>,
+    /// Maximum number of tokens when decoding .torrent files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bdecode_token_limit: Option<i64>,
+
+    /* libtorrent */
+    /// Network interface used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_network_interface: Option<String>,
+    /// IP Address to bind to. Empty String means All addresses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current_interface_address: Option<String>,
+    /// Save resume data interval in min
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub save_resume_data_interval: Option<i64>,
+    /// Number of asynchronous I/O threads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub async_io_threads: Option<i64>,
+    /// Number of hashing threads
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hashing_threads: Option<i64>,
+    /// File pool size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_pool_size: Option<i64>,
+    /// Outstanding memory when checking torrents in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checking_memory_use: Option<i64>,
+    /// Disk cache used in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_cache: Option<i64>,
+    /// Disk cache expiry interval in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_cache_ttl: Option<i64>,
+    /// Disk queue size (bytes)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_queue_size: Option<i64>,
+    /// Disk IO type: 0 default, 1 memory mapped files, 2 POSIX-compliant
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_io_type: Option<i64>,
+    /// Disk IO read mode: 0 disable OS cache, 1 enable OS cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_io_read_mode: Option<i64>,
+    /// Disk IO write mode: 0 disable OS cache, 1 enable OS cache, 2 write-through
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk_io_write_mode: Option<i64>,
+    /// Enable OS cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_os_cache: Option<bool>,
+    /// Enable coalescing reads & writes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_coalesce_read_write: Option<bool>,
+    /// Enable sending of upload piece suggestions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_upload_suggestions: Option<bool>,
+    /// Send buffer watermark in KiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_buffer_watermark: Option<i64>,
+    /// Send buffer low watermark in KiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_buffer_low_watermark: Option<i64>,
+    /// Send buffer watermark factor in percent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub send_buffer_watermark_factor: Option<i64>,
+    /// Outgoing connections per second
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connection_speed: Option<i64>,
+    /// Socket send buffer size (bytes), 0 for system default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_send_buffer_size: Option<i64>,
+    /// Socket receive buffer size (bytes), 0 for system default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_receive_buffer_size: Option<i64>,
+    /// Socket backlog size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub socket_backlog_size: Option<i64>,
+    /// Outgoing ports (min port range)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outgoing_ports_min: Option<i64>,
+    /// Outgoing ports (max port range)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outgoing_ports_max: Option<i64>,
+    /// UPnP lease duration (0: Permanent lease)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upnp_lease_duration: Option<i64>,
+    /// Type of service (ToS) for connections to peers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_tos: Option<i64>,
+    /// μTP-TCP mixed mode algorithm: 0 prefer TCP, 1 peer proportional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utp_tcp_mixed_mode: Option<i64>,
+    /// True allows multiple connections from the same IP address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_multi_connections_from_same_ip: Option<bool>,
+    /// True enables the embedded tracker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enable_embedded_tracker: Option<bool>,
+    /// Embedded tracker port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded_tracker_port: Option<i64>,
+    /// True if the embedded tracker port is forwarded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedded_tracker_port_forwarding: Option<bool>,
+    /// Upload slots behavior used: 0 fixed slots, 1 upload rate based
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_slots_behavior: Option<i64>,
+    /// Upload choking algorithm used: 0 round-robin, 1 fastest upload, 2 anti-leech
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_choking_algorithm: Option<i64>,
+    /// True always announce to all trackers in a tier
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announce_to_all_trackers: Option<bool>,
+    /// True always announce to all tiers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announce_to_all_tiers: Option<bool>,
+    /// IP announced to trackers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub announce_ip: Option<String>,
+    /// Maximum number of concurrent HTTP announces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_http_announces: Option<i64>,
+    /// True if torrents are reannounced when the IP or port changes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reannounce_when_address_changed: Option<bool>,
+    /// Maximum number of outstanding requests to a peer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_queue_size: Option<i64>,
+    /// Percentage of peers to disconnect on turnover
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_turnover: Option<i64>,
+    /// Peer turnover threshold percentage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_turnover_cutoff: Option<i64>,
+    /// Peer turnover disconnect interval (seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer_turnover_interval: Option<i64>,
+    /// Comma-separated list of DHT bootstrap nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dht_bootstrap_nodes: Option<String>,
+    /// True if I2P is enabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub i2p_enabled: Option<bool>,
+    /// I2P SAM bridge address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub i2p_address: Option<String>,
+    /// I2P SAM bridge port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub i2p_port: Option<i64>,
+    /// True if I2P and regular peers may be mixed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub i2p_mixed_mode: Option<bool>,
+
+    /// Keys not known to this crate
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Preferences {
+    /// Fields of `self` which differ from `base`, as the JSON object expected
+    /// by `app/setPreferences`.
+    pub fn changes_from(&self, base: &Preferences) -> Result<Map<String, Value>> {
+        let Value::Object(new) = serde_json::to_value(self)? else {
+            unreachable!("Preferences always serializes to an object");
+        };
+        let Value::Object(old) = serde_json::to_value(base)? else {
+            unreachable!("Preferences always serializes to an object");
+        };
+
+        let changes = new
+            .into_iter()
+            .filter(|(key, value)| old.get(key) != Some(value))
+            .collect();
+        Ok(changes)
+    }
+}
+
+impl Client {
+    pub fn get_preferences(&self) -> Result<Preferences> {
+        let resp = self.send(&Endpoint::preferences())?;
+        let result: Preferences = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Send the fields of `prefs` which differ from the current preferences
+    /// of the server, fetched first. Use `update_preferences` to diff against
+    /// preferences already at hand instead.
+    pub fn set_preferences(&self, prefs: &Preferences) -> Result<()> {
+        let base = self.get_preferences()?;
+        self.update_preferences(&base, prefs)
+    }
+
+    /// Send only the fields of `prefs` which differ from `base`, typically
+    /// the value returned by `get_preferences` before it was modified.
+    pub fn update_preferences(&self, base: &Preferences, prefs: &Preferences) -> Result<()> {
+        let changes = prefs.changes_from(base)?;
+        if changes.is_empty() {
+            return Ok(());
+        }
+        self.set_preferences_raw(&changes)
+    }
+
+    fn set_preferences_raw(&self, changes: &Map<String, Value>) -> Result<()> {
+        self.send(&Endpoint::set_preferences(changes)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefs(value: Value) -> Preferences {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn keeps_unknown_keys() {
+        let p = prefs(serde_json::json!({"locale": "en", "new_option": 3}));
+        assert_eq!(p.locale.as_deref(), Some("en"));
+        assert_eq!(p.extra.get("new_option"), Some(&Value::from(3)));
+    }
+
+    #[test]
+    fn changes_only_include_modified_fields() {
+        let base = prefs(serde_json::json!({
+            "locale": "en",
+            "dht": true,
+            "new_option": 3,
+            "other_option": "x",
+        }));
+        let mut new = base.clone();
+        new.dht = Some(false);
+        new.extra.insert("new_option".to_string(), Value::from(4));
+
+        let changes = new.changes_from(&base).unwrap();
+        let expected = serde_json::json!({"dht": false, "new_option": 4});
+        assert_eq!(Value::Object(changes), expected);
+        assert!(base.changes_from(&base).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+use strum_macros::AsRefStr;
+
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::{Endpoint, STOP_START_API_VERSION};
+use crate::qbt::error::Result;
+use crate::qbt::torrents::{Torrent, TorrentInfo};
+
+/// State filter of `torrents/info`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum TorrentFilter {
+    All,
+    Downloading,
+    Seeding,
+    Completed,
+    /// "paused" before Web API 2.11.0
+    Stopped,
+    /// "resumed" before Web API 2.11.0
+    Running,
+    Active,
+    Inactive,
+    Stalled,
+    StalledUploading,
+    StalledDownloading,
+    Checking,
+    Moving,
+    Errored,
+}
+
+impl TorrentFilter {
+    fn name(&self, stop_start: bool) -> &str {
+        match self {
+            Self::Stopped if !stop_start => "paused",
+            Self::Running if !stop_start => "resumed",
+            x => x.as_ref(),
+        }
+    }
+}
+
+/// Builder for the server side filtering, sorting and pagination of
+/// `torrents/info`. The default query returns every torrent.
+#[derive(Debug, Clone, Default)]
+pub struct TorrentListQuery {
+    filter: Option<TorrentFilter>,
+    category: Option<String>,
+    tag: Option<String>,
+    sort: Option<String>,
+    reverse: Option<bool>,
+    limit: Option<u64>,
+    offset: Option<u64>,
+    hashes: Vec<String>,
+    private: Option<bool>,
+}
+
+impl TorrentListQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn filter(mut self, filter: TorrentFilter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Only torrents of `category`, an empty string matching torrents without
+    /// category.
+    pub fn category(mut self, category: &str) -> Self {
+        self.category = Some(category.to_string());
+        self
+    }
+
+    /// Only torrents tagged `tag`, an empty string matching torrents without
+    /// tags.
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.tag = Some(tag.to_string());
+        self
+    }
+
+    /// Sort by a field of `TorrentInfo`, e.g. "added_on".
+    pub fn sort(mut self, field: &str) -> Self {
+        self.sort = Some(field.to_string());
+        self
+    }
+
+    pub fn reverse(mut self, value: bool) -> Self {
+        self.reverse = Some(value);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Only the torrents of `hashes`. May be called several times.
+    pub fn hashes(mut self, hashes: &[&str]) -> Self {
+        self.hashes.extend(hashes.iter().map(|x| x.to_string()));
+        self
+    }
+
+    /// Only private (or only public) torrents. Requires Web API 2.11.1.
+    pub fn private(mut self, value: bool) -> Self {
+        self.private = Some(value);
+        self
+    }
+
+    /// Query parameters. `stop_start` selects the filter names of Web API
+    /// 2.11.0 and later.
+    pub(super) fn params(&self, stop_start: bool) -> Vec<(&'static str, String)> {
+        let mut params: Vec<(&'static str, String)> = vec![];
+        if let Some(x) = &self.filter {
+            params.push(("filter", x.name(stop_start).to_string()));
+        }
+        if let Some(x) = &self.category {
+            params.push(("category", x.clone()));
+        }
+        if let Some(x) = &self.tag {
+            params.push(("tag", x.clone()));
+        }
+        if let Some(x) = &self.sort {
+            params.push(("sort", x.clone()));
+        }
+        if let Some(x) = self.reverse {
+            params.push(("reverse", x.to_string()));
+        }
+        if let Some(x) = self.limit {
+            params.push(("limit", x.to_string()));
+        }
+        if let Some(x) = self.offset {
+            params.push(("offset", x.to_string()));
+        }
+        if !self.hashes.is_empty() {
+            params.push(("hashes", self.hashes.join("|")));
+        }
+        if let Some(x) = self.private {
+            params.push(("private", x.to_string()));
+        }
+        params
+    }
+
+    /// Whether the filter name depends on the Web API version.
+    pub(super) fn needs_api_version(&self) -> bool {
+        matches!(
+            self.filter,
+            Some(TorrentFilter::Stopped | TorrentFilter::Running)
+        )
+    }
+}
+
+/// Iterator over the torrents of a query, fetching `page_size` torrents per
+/// request so the whole list is never held in memory.
+pub struct TorrentPages {
+    client: Client,
+    query: TorrentListQuery,
+    page_size: u64,
+    offset: u64,
+    /// Torrents left before reaching the limit of the query, if any
+    remaining: Option<u64>,
+    done: bool,
+}
+
+impl TorrentPages {
+    /// Number of torrents to ask for in the next request.
+    fn page_limit(&self) -> u64 {
+        match self.remaining {
+            Some(x) => x.min(self.page_size),
+            None => self.page_size,
+        }
+    }
+
+    /// Move past a page of `count` torrents, received for `limit`. A short
+    /// page is the last one.
+    fn advance(&mut self, limit: u64, count: u64) {
+        self.offset += count;
+        self.remaining = self.remaining.map(|x| x - count.min(x));
+        self.done = count < limit || self.remaining == Some(0);
+    }
+}
+
+impl Iterator for TorrentPages {
+    type Item = Result<Vec<Torrent>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let limit = self.page_limit();
+        let query = self.query.clone().offset(self.offset).limit(limit);
+        let infos = match self.client.query_torrent_infos(&query) {
+            Ok(x) => x,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+
+        let count = infos.len() as u64;
+        self.advance(limit, count);
+        if count == 0 {
+            return None;
+        }
+
+        let client = &self.client;
+        Some(Ok(infos
+            .into_iter()
+            .map(|x| Torrent::new(client, x))
+            .collect()))
+    }
+}
+
+impl Client {
+    pub(super) fn query_torrent_infos(&self, query: &TorrentListQuery) -> Result<Vec<TorrentInfo>> {
+        let stop_start = match query.needs_api_version() {
+            true => self.api_version_at_least(STOP_START_API_VERSION)?,
+            false => true,
+        };
+        let resp = self.send(&Endpoint::torrent_query(query, stop_start))?;
+        parse_json(resp)
+    }
+
+    /// Torrents matching `query`, filtered and sorted by the server.
+    pub fn query_torrents<C>(&self, query: &TorrentListQuery, container: &mut C) -> Result<()>
+    where
+        C: Extend<Torrent>,
+    {
+        let infos = self.query_torrent_infos(query)?;
+        container.extend(infos.into_iter().map(|x| Torrent::new(self, x)));
+        Ok(())
+    }
+
+    /// Walk the torrents matching `query` in pages of `page_size`, starting at
+    /// its offset and stopping at its limit.
+    ///
+    /// Pages are only consistent if the order is stable, so the torrents are
+    /// sorted by hash unless the query sets another order.
+    pub fn torrent_pages(&self, query: &TorrentListQuery, page_size: u64) -> TorrentPages {
+        let mut query = query.clone();
+        if query.sort.is_none() {
+            query.sort = Some("hash".to_string());
+        }
+        TorrentPages {
+            client: self.clone(),
+            offset: query.offset.unwrap_or(0),
+            remaining: query.limit,
+            // The server takes a zero limit as no limit at all
+            done: query.limit == Some(0),
+            query,
+            page_size: page_size.max(1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_params() {
+        let query = TorrentListQuery::new()
+            .filter(TorrentFilter::StalledUploading)
+            .category("")
+            .sort("added_on")
+            .reverse(true)
+            .hashes(&["a", "b"])
+            .hashes(&["c"]);
+        assert!(!query.needs_api_version());
+        assert_eq!(
+            query.params(true),
+            [
+                ("filter", "stalled_uploading".to_string()),
+                ("category", String::new()),
+                ("sort", "added_on".to_string()),
+                ("reverse", "true".to_string()),
+                ("hashes", "a|b|c".to_string()),
+            ]
+        );
+        assert!(TorrentListQuery::new().params(true).is_empty());
+    }
+
+    #[test]
+    fn filter_names_by_version() {
+        let query = TorrentListQuery::new().filter(TorrentFilter::Stopped);
+        assert!(query.needs_api_version());
+        assert_eq!(query.params(true), [("filter", "stopped".to_string())]);
+        assert_eq!(query.params(false), [("filter", "paused".to_string())]);
+        assert_eq!(TorrentFilter::Running.name(false), "resumed");
+        assert_eq!(TorrentFilter::Running.name(true), "running");
+    }
+
+    fn pages(query: TorrentListQuery, page_size: u64) -> TorrentPages {
+        let client = Client::new("http://localhost:8080", "admin", "adminadmin", true).unwrap();
+        client.torrent_pages(&query, page_size)
+    }
+
+    #[test]
+    fn pages_until_short_page() {
+        let mut pages = pages(TorrentListQuery::new().offset(10), 4);
+        assert_eq!(pages.query.sort.as_deref(), Some("hash"));
+        assert_eq!(pages.page_limit(), 4);
+
+        pages.advance(4, 4);
+        assert!(!pages.done);
+        assert_eq!(pages.offset, 14);
+        pages.advance(4, 1);
+        assert!(pages.done);
+        assert_eq!(pages.offset, 15);
+    }
+
+    #[test]
+    fn pages_stop_at_limit() {
+        let mut pages = pages(TorrentListQuery::new().sort("name").limit(5), 2);
+        assert_eq!(pages.query.sort.as_deref(), Some("name"));
+
+        pages.advance(pages.page_limit(), 2);
+        pages.advance(pages.page_limit(), 2);
+        assert!(!pages.done);
+        // Only one torrent left below the limit.
+        assert_eq!(pages.page_limit(), 1);
+        pages.advance(1, 1);
+        assert!(pages.done);
+        assert_eq!(pages.remaining, Some(0));
+    }
+
+    #[test]
+    fn zero_limit() {
+        let mut pages = pages(TorrentListQuery::new().limit(0), 4);
+        assert!(pages.done);
+        assert!(pages.next().is_none());
+    }
+
+    #[test]
+    fn zero_page_size() {
+        assert_eq!(pages(TorrentListQuery::new(), 0).page_limit(), 1);
+    }
+}
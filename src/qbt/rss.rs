@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
+
+/// Separator of the components of an RSS item path, e.g. "Folder\Feed".
+pub const RSS_PATH_SEPARATOR: &str = "\\";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct RssArticle {
+    /// Article id, unique within its feed
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// Publication date, as sent by the feed
+    pub date: String,
+    pub link: String,
+    /// URL of the .torrent file or magnet link
+    #[serde(rename = "torrentURL")]
+    pub torrent_url: String,
+    pub author: String,
+    pub category: String,
+    #[serde(rename = "isRead")]
+    pub is_read: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RssFeed {
+    /// Feed unique id
+    pub uid: String,
+    /// Feed URL
+    pub url: String,
+    /// Feed title. Only set when requested with data.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Date of the last feed build. Only set when requested with data.
+    #[serde(
+        default,
+        rename = "lastBuildDate",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_build_date: Option<String>,
+    /// True while the feed is being refreshed
+    #[serde(default, rename = "isLoading")]
+    pub is_loading: bool,
+    /// True if the last refresh failed
+    #[serde(default, rename = "hasError")]
+    pub has_error: bool,
+    /// Articles of the feed. Only set when requested with data.
+    #[serde(default)]
+    pub articles: Vec<RssArticle>,
+}
+
+/// Node of the RSS tree: either a feed or a folder of named items.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum RssItem {
+    Feed(RssFeed),
+    Folder(HashMap<String, RssItem>),
+}
+
+/// Every feed below `folder`, with its full item path.
+pub fn rss_feeds(folder: &HashMap<String, RssItem>) -> Vec<(String, &RssFeed)> {
+    let mut feeds = vec![];
+    for (name, item) in folder {
+        match item {
+            RssItem::Feed(feed) => feeds.push((name.clone(), feed)),
+            RssItem::Folder(children) => {
+                for (path, feed) in rss_feeds(children) {
+                    feeds.push(([name.as_str(), &path].join(RSS_PATH_SEPARATOR), feed));
+                }
+            }
+        }
+    }
+    feeds
+}
+
+/// RSS auto-downloading rule. Keys unknown to this crate, e.g. the
+/// `torrentParams` of newer servers, are kept in `extra`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RssRule {
+    /// Whether the rule is enabled
+    pub enabled: bool,
+    /// The substring that the torrent name must contain
+    pub must_contain: String,
+    /// The substring that the torrent name must not contain
+    pub must_not_contain: String,
+    /// Enable regex mode in `must_contain` and `must_not_contain`
+    pub use_regex: bool,
+    /// Episode filter definition
+    pub episode_filter: String,
+    /// Enable smart episode filter
+    pub smart_filter: bool,
+    /// The list of episode IDs already matched by smart filter
+    pub previously_matched_episodes: Vec<String>,
+    /// The feed URLs the rule applies to
+    pub affected_feeds: Vec<String>,
+    /// Ignore subsequent rule matches (days)
+    pub ignore_days: i64,
+    /// The rule last match time
+    pub last_match: String,
+    /// Add matched torrent in paused mode, `None` to use the global setting
+    pub add_paused: Option<bool>,
+    /// Assign category to the torrent
+    pub assigned_category: String,
+    /// Save torrent to the given directory
+    pub save_path: String,
+    /// Keys not known to this crate
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Client {
+    /// The whole RSS tree. With `with_data`, feeds include their articles.
+    pub fn get_rss_items(&self, with_data: bool) -> Result<HashMap<String, RssItem>> {
+        let resp = self.send(&Endpoint::rss_items(with_data))?;
+        let result: HashMap<String, RssItem> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Create a folder, e.g. "Linux\ISOs".
+    pub fn add_rss_folder(&self, path: &str) -> Result<()> {
+        self.send(&Endpoint::rss_add_folder(path))?;
+        Ok(())
+    }
+
+    /// Subscribe to `url` as the item `path`. An empty `path` uses the feed
+    /// title at the root.
+    pub fn add_rss_feed(&self, url: &str, path: &str) -> Result<()> {
+        self.send(&Endpoint::rss_add_feed(url, path))?;
+        Ok(())
+    }
+
+    /// Remove a feed or a folder with everything below it.
+    pub fn remove_rss_item(&self, path: &str) -> Result<()> {
+        self.send(&Endpoint::rss_remove_item(path))?;
+        Ok(())
+    }
+
+    pub fn move_rss_item(&self, item_path: &str, dest_path: &str) -> Result<()> {
+        self.send(&Endpoint::rss_move_item(item_path, dest_path))?;
+        Ok(())
+    }
+
+    pub fn refresh_rss_item(&self, item_path: &str) -> Result<()> {
+        self.send(&Endpoint::rss_refresh_item(item_path))?;
+        Ok(())
+    }
+
+    /// Mark an article as read, or every article of the item if `article_id`
+    /// is `None`.
+    pub fn mark_rss_as_read(&self, item_path: &str, article_id: Option<&str>) -> Result<()> {
+        self.send(&Endpoint::rss_mark_as_read(item_path, article_id))?;
+        Ok(())
+    }
+
+    /// Every auto-downloading rule, by name.
+    pub fn get_rss_rules(&self) -> Result<HashMap<String, RssRule>> {
+        let resp = self.send(&Endpoint::rss_rules())?;
+        let result: HashMap<String, RssRule> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Create or replace the rule `name`.
+    pub fn set_rss_rule(&self, name: &str, rule: &RssRule) -> Result<()> {
+        self.send(&Endpoint::rss_set_rule(name, rule)?)?;
+        Ok(())
+    }
+
+    pub fn rename_rss_rule(&self, name: &str, new_name: &str) -> Result<()> {
+        self.send(&Endpoint::rss_rename_rule(name, new_name))?;
+        Ok(())
+    }
+
+    pub fn remove_rss_rule(&self, name: &str) -> Result<()> {
+        self.send(&Endpoint::rss_remove_rule(name))?;
+        Ok(())
+    }
+
+    /// Titles of the articles matched by the rule `name`, by feed name.
+    pub fn get_rss_matching_articles(&self, name: &str) -> Result<HashMap<String, Vec<String>>> {
+        let resp = self.send(&Endpoint::rss_matching_articles(name))?;
+        let result: HashMap<String, Vec<String>> = parse_json(resp)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn rss_tree() {
+        let items: HashMap<String, RssItem> = serde_json::from_value(json!({
+            "Debian": { "uid": "{1}", "url": "https://debian.example/rss" },
+            "Linux": {
+                "ISOs": {
+                    "Arch": {
+                        "uid": "{2}",
+                        "url": "https://arch.example/rss",
+                        "title": "Arch Linux",
+                        "isLoading": true,
+                        "articles": [{ "id": "a", "title": "2024.01.01", "isRead": true }]
+                    }
+                },
+                "Empty": {}
+            }
+        }))
+        .unwrap();
+
+        let mut feeds = rss_feeds(&items);
+        feeds.sort_by(|a, b| a.0.cmp(&b.0));
+        let paths: Vec<&str> = feeds.iter().map(|x| x.0.as_str()).collect();
+        assert_eq!(paths, ["Debian", "Linux\\ISOs\\Arch"]);
+
+        let arch = feeds[1].1;
+        assert_eq!(arch.title.as_deref(), Some("Arch Linux"));
+        assert!(arch.is_loading && !arch.has_error);
+        assert!(arch.articles[0].is_read);
+        assert_eq!(feeds[0].1.title, None);
+        assert!(matches!(&items["Linux"], RssItem::Folder(x) if x.len() == 2));
+    }
+
+    #[test]
+    fn rule_keeps_unknown_keys() {
+        let value = json!({
+            "enabled": true,
+            "mustContain": "1080p",
+            "affectedFeeds": ["https://arch.example/rss"],
+            "addPaused": null,
+            "torrentParams": { "category": "iso", "stopped": false }
+        });
+        let rule: RssRule = serde_json::from_value(value.clone()).unwrap();
+        assert!(rule.enabled);
+        assert_eq!(rule.must_contain, "1080p");
+        assert_eq!(rule.add_paused, None);
+        assert_eq!(rule.extra["torrentParams"]["category"], "iso");
+
+        let json = serde_json::to_value(&rule).unwrap();
+        assert_eq!(json["torrentParams"], value["torrentParams"]);
+        assert_eq!(json["mustContain"], "1080p");
+        assert_eq!(serde_json::from_value::<RssRule>(json).unwrap(), rule);
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, TimeZone, Weekday};
+use log::info;
+use std::thread;
+
+use crate::qbt::core::Client;
+use crate::qbt::error::Result;
+use crate::qbt::transfer::ByteRate;
+
+/// Limits applied on `days` between `start` and `end`. If `end` is not after
+/// `start` the rule spans midnight, and `days` are the days it starts on.
+#[derive(Debug, Clone)]
+pub struct BandwidthRule {
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub download: ByteRate,
+    pub upload: ByteRate,
+}
+
+impl BandwidthRule {
+    fn is_active(&self, at: DateTime<Local>) -> bool {
+        let time = at.time();
+        let today = at.weekday();
+        let yesterday = today.pred();
+
+        if self.start < self.end {
+            self.days.contains(&today) && time >= self.start && time < self.end
+        } else {
+            (self.days.contains(&today) && time >= self.start)
+                || (self.days.contains(&yesterday) && time < self.end)
+        }
+    }
+}
+
+/// Weekly bandwidth plan. The first active rule wins, and the default limits
+/// apply when no rule is active.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthPlan {
+    pub default_download: ByteRate,
+    pub default_upload: ByteRate,
+    pub rules: Vec<BandwidthRule>,
+}
+
+impl BandwidthPlan {
+    pub fn new(default_download: ByteRate, default_upload: ByteRate) -> Self {
+        Self {
+            default_download,
+            default_upload,
+            rules: vec![],
+        }
+    }
+
+    pub fn rule(
+        mut self,
+        days: &[Weekday],
+        start: NaiveTime,
+        end: NaiveTime,
+        download: ByteRate,
+        upload: ByteRate,
+    ) -> Self {
+        self.rules.push(BandwidthRule {
+            days: days.to_vec(),
+            start,
+            end,
+            download,
+            upload,
+        });
+        self
+    }
+
+    /// (download, upload) limits in effect at `at`.
+    pub fn limits_at(&self, at: DateTime<Local>) -> (ByteRate, ByteRate) {
+        match self.rules.iter().find(|x| x.is_active(at)) {
+            Some(rule) => (rule.download, rule.upload),
+            None => (self.default_download, self.default_upload),
+        }
+    }
+
+    /// Next time after `after` at which a rule starts or ends on one of its
+    /// days, or `None` if the plan has no rules.
+    pub fn next_change(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let today = after.date_naive();
+        // Starting yesterday, for rules spanning midnight that end today.
+        (-1..8)
+            .filter_map(|x| today.checked_add_signed(Duration::days(x)))
+            .flat_map(|day| {
+                self.rules
+                    .iter()
+                    .filter(move |x| x.days.contains(&day.weekday()))
+                    .flat_map(move |x| {
+                        let end_day = match x.start < x.end {
+                            true => Some(day),
+                            false => day.succ_opt(),
+                        };
+                        let end = end_day.map(|end_day| end_day.and_time(x.end));
+                        [Some(day.and_time(x.start)), end]
+                    })
+            })
+            .flatten()
+            .filter_map(|x| Local.from_local_datetime(&x).earliest())
+            .filter(|x| *x > after)
+            .min()
+    }
+}
+
+impl Client {
+    /// Set the global limits to the ones `plan` has in effect at `at`, if
+    /// they differ from the current ones.
+    pub fn apply_bandwidth_plan(&self, plan: &BandwidthPlan, at: DateTime<Local>) -> Result<()> {
+        let (download, upload) = plan.limits_at(at);
+
+        if self.get_global_download_limit()? != download {
+            info!("Setting global download limit to {} B/s", download);
+            self.set_global_download_limit(download)?;
+        }
+        if self.get_global_upload_limit()? != upload {
+            info!("Setting global upload limit to {} B/s", upload);
+            self.set_global_upload_limit(upload)?;
+        }
+
+        Ok(())
+    }
+
+    /// Apply `plan` now, then each time it changes. Blocks forever unless the
+    /// plan has no rules or a request fails.
+    pub fn run_bandwidth_plan(&self, plan: &BandwidthPlan) -> Result<()> {
+        loop {
+            let now = Local::now();
+            self.apply_bandwidth_plan(plan, now)?;
+
+            let Some(next) = plan.next_change(now) else {
+                return Ok(());
+            };
+            thread::sleep((next - now).to_std().unwrap_or_default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, hour: u32, min: u32) -> DateTime<Local> {
+        // 2024-01-01 is a Monday.
+        Local.with_ymd_and_hms(2024, 1, day, hour, min, 0).unwrap()
+    }
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    fn plan() -> BandwidthPlan {
+        BandwidthPlan::new(ByteRate::UNLIMITED, ByteRate::UNLIMITED)
+            .rule(
+                &[Weekday::Mon],
+                time(9),
+                time(17),
+                ByteRate::kib(100),
+                ByteRate::kib(10),
+            )
+            .rule(
+                &[Weekday::Fri],
+                time(22),
+                time(6),
+                ByteRate::mib(1),
+                ByteRate::kib(500),
+            )
+    }
+
+    #[test]
+    fn daytime_rule() {
+        let plan = plan();
+        let limited = (ByteRate::kib(100), ByteRate::kib(10));
+        let unlimited = (ByteRate::UNLIMITED, ByteRate::UNLIMITED);
+        assert_eq!(plan.limits_at(at(1, 9, 0)), limited);
+        assert_eq!(plan.limits_at(at(1, 16, 59)), limited);
+        assert_eq!(plan.limits_at(at(1, 17, 0)), unlimited);
+        assert_eq!(plan.limits_at(at(2, 12, 0)), unlimited);
+    }
+
+    #[test]
+    fn rule_spanning_midnight() {
+        let plan = plan();
+        let night = (ByteRate::mib(1), ByteRate::kib(500));
+        // Friday 2024-01-05 22:00 to Saturday 06:00
+        assert_eq!(plan.limits_at(at(5, 23, 0)), night);
+        assert_eq!(plan.limits_at(at(6, 5, 59)), night);
+        assert_ne!(plan.limits_at(at(6, 6, 0)), night);
+        assert_ne!(plan.limits_at(at(5, 5, 0)), night);
+    }
+
+    #[test]
+    fn next_change() {
+        let plan = plan();
+        assert_eq!(plan.next_change(at(1, 8, 0)), Some(at(1, 9, 0)));
+        assert_eq!(plan.next_change(at(1, 9, 0)), Some(at(1, 17, 0)));
+        assert_eq!(plan.next_change(at(1, 17, 0)), Some(at(5, 22, 0)));
+        assert_eq!(plan.next_change(at(6, 1, 0)), Some(at(6, 6, 0)));
+        assert_eq!(BandwidthPlan::default().next_change(at(1, 8, 0)), None);
+    }
+}
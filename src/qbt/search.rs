@@ -0,0 +1,329 @@
+use serde::{Deserialize, Serialize};
+use std::thread;
+use std::time::Duration;
+
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchJobState {
+    Running,
+    Stopped,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchStatus {
+    /// ID of the search job
+    pub id: i64,
+    /// Current status of the search job
+    pub status: SearchJobState,
+    /// Total number of results. If the status is `Running` this number may
+    /// continue to increase
+    pub total: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResult {
+    /// URL of the torrent's description page
+    pub descr_link: String,
+    /// Name of the file
+    pub file_name: String,
+    /// Size of the file in Bytes
+    pub file_size: i64,
+    /// Torrent download link (usually either .torrent file or magnet link)
+    pub file_url: String,
+    /// Number of leechers
+    pub nb_leechers: i64,
+    /// Number of seeders
+    pub nb_seeders: i64,
+    /// URL of the torrent site
+    pub site_url: String,
+    /// Name of the plugin which found the result
+    #[serde(default)]
+    pub engine_name: String,
+    /// Publication date (Unix Epoch), 0 if unknown
+    #[serde(default)]
+    pub pub_date: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchResults {
+    /// Results of the page
+    pub results: Vec<SearchResult>,
+    /// Current status of the search job
+    pub status: SearchJobState,
+    /// Total number of results. If the status is `Running` this number may
+    /// continue to increase
+    pub total: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SearchCategory {
+    /// Category id, as accepted by `Client::start_search`
+    pub id: String,
+    /// Human readable category name
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchPlugin {
+    /// Whether the plugin is enabled
+    pub enabled: bool,
+    /// Full name of the plugin
+    pub full_name: String,
+    /// Short name of the plugin
+    pub name: String,
+    /// List of category objects
+    pub supported_categories: Vec<SearchCategory>,
+    /// URL of the torrent site
+    pub url: String,
+    /// Installed version of the plugin
+    pub version: String,
+}
+
+impl SearchResults {
+    /// Whether no result is left after `offset`, the offset past this page.
+    /// A stopped job returning an empty page is finished even if it was short
+    /// of `total`, otherwise it would be polled forever.
+    fn is_last_page(&self, offset: i64) -> bool {
+        self.status == SearchJobState::Stopped && (offset >= self.total || self.results.is_empty())
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchJob {
+    id: i64,
+}
+
+/// Blocking iterator over the result pages of a search job, polling every
+/// `poll_interval` until the job is stopped and every result was returned.
+/// The job is deleted when the iterator is dropped.
+pub struct SearchStream {
+    client: Client,
+    id: i64,
+    offset: i64,
+    page_size: i64,
+    poll_interval: Duration,
+    done: bool,
+}
+
+impl SearchStream {
+    /// ID of the search job
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+}
+
+impl Iterator for SearchStream {
+    type Item = Result<Vec<SearchResult>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let page = match self.client.get_search_results(
+                self.id,
+                Some(self.page_size),
+                Some(self.offset),
+            ) {
+                Ok(x) => x,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+
+            self.offset += page.results.len() as i64;
+            if page.is_last_page(self.offset) {
+                self.done = true;
+            }
+
+            if !page.results.is_empty() {
+                return Some(Ok(page.results));
+            }
+            if !self.done {
+                thread::sleep(self.poll_interval);
+            }
+        }
+        None
+    }
+}
+
+impl Drop for SearchStream {
+    fn drop(&mut self) {
+        let _ = self.client.delete_search(self.id);
+    }
+}
+
+impl Client {
+    /// Start a search job and return its id. `plugins` is "all", "enabled" or
+    /// a list of plugin names, and `category` a `SearchCategory::id` or "all".
+    pub fn start_search(&self, pattern: &str, plugins: &[&str], category: &str) -> Result<i64> {
+        let resp = self.send(&Endpoint::search_start(pattern, plugins, category))?;
+        let job: SearchJob = parse_json(resp)?;
+        Ok(job.id)
+    }
+
+    pub fn stop_search(&self, id: i64) -> Result<()> {
+        self.send(&Endpoint::search_stop(id))?;
+        Ok(())
+    }
+
+    /// Status of the search job `id`, or of every job if `id` is `None`.
+    pub fn get_search_status(&self, id: Option<i64>) -> Result<Vec<SearchStatus>> {
+        let resp = self.send(&Endpoint::search_status(id))?;
+        let result: Vec<SearchStatus> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    pub fn get_search_results(
+        &self,
+        id: i64,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<SearchResults> {
+        let resp = self.send(&Endpoint::search_results(id, limit, offset))?;
+        let result: SearchResults = parse_json(resp)?;
+        Ok(result)
+    }
+
+    pub fn delete_search(&self, id: i64) -> Result<()> {
+        self.send(&Endpoint::search_delete(id))?;
+        Ok(())
+    }
+
+    /// Start a search and stream its results, `page_size` at most at a time.
+    pub fn search(
+        &self,
+        pattern: &str,
+        plugins: &[&str],
+        category: &str,
+        page_size: i64,
+        poll_interval: Duration,
+    ) -> Result<SearchStream> {
+        let id = self.start_search(pattern, plugins, category)?;
+        Ok(SearchStream {
+            client: self.clone(),
+            id,
+            offset: 0,
+            page_size,
+            poll_interval,
+            done: false,
+        })
+    }
+
+    pub fn get_search_plugins(&self) -> Result<Vec<SearchPlugin>> {
+        let resp = self.send(&Endpoint::search_plugins())?;
+        let result: Vec<SearchPlugin> = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// Install plugins from URLs or file paths on the server.
+    pub fn install_search_plugins(&self, sources: &[&str]) -> Result<()> {
+        self.send(&Endpoint::search_install_plugins(sources))?;
+        Ok(())
+    }
+
+    pub fn uninstall_search_plugins(&self, names: &[&str]) -> Result<()> {
+        self.send(&Endpoint::search_uninstall_plugins(names))?;
+        Ok(())
+    }
+
+    pub fn enable_search_plugins(&self, names: &[&str], enable: bool) -> Result<()> {
+        self.send(&Endpoint::search_enable_plugins(names, enable))?;
+        Ok(())
+    }
+
+    pub fn update_search_plugins(&self) -> Result<()> {
+        self.send(&Endpoint::search_update_plugins())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_results() {
+        let results: SearchResults = serde_json::from_value(json!({
+            "results": [{
+                "descrLink": "https://site.example/t/1",
+                "fileName": "debian-12.iso",
+                "fileSize": 658505728,
+                "fileUrl": "magnet:?xt=urn:btih:a",
+                "nbLeechers": 3,
+                "nbSeeders": 120,
+                "siteUrl": "https://site.example",
+                "engineName": "example",
+                "pubDate": 1700000000
+            }, {
+                // Older servers send neither the engine name nor the
+                // publication date.
+                "descrLink": "",
+                "fileName": "debian-11.iso",
+                "fileSize": -1,
+                "fileUrl": "https://site.example/t/2.torrent",
+                "nbLeechers": 0,
+                "nbSeeders": 0,
+                "siteUrl": "https://site.example"
+            }],
+            "status": "Running",
+            "total": 2
+        }))
+        .unwrap();
+        assert_eq!(results.status, SearchJobState::Running);
+        assert_eq!(results.results[0].nb_seeders, 120);
+        assert_eq!(results.results[0].engine_name, "example");
+        assert_eq!(results.results[1].engine_name, "");
+        assert_eq!(results.results[1].pub_date, 0);
+    }
+
+    #[test]
+    fn last_page() {
+        let page = |status: &str, results: usize, total: i64| -> SearchResults {
+            let result = json!({
+                "descrLink": "",
+                "fileName": "debian-12.iso",
+                "fileSize": 658505728,
+                "fileUrl": "magnet:?xt=urn:btih:a",
+                "nbLeechers": 0,
+                "nbSeeders": 0,
+                "siteUrl": "https://site.example"
+            });
+            serde_json::from_value(json!({
+                "results": vec![result; results],
+                "status": status,
+                "total": total
+            }))
+            .unwrap()
+        };
+        assert!(!page("Running", 0, 10).is_last_page(10));
+        assert!(!page("Stopped", 5, 10).is_last_page(5));
+        assert!(page("Stopped", 5, 10).is_last_page(10));
+        // The server reported more results than it returned
+        assert!(page("Stopped", 0, 10).is_last_page(5));
+    }
+
+    #[test]
+    fn parses_status_and_plugins() {
+        let status: Vec<SearchStatus> =
+            serde_json::from_value(json!([{ "id": 12, "status": "Stopped", "total": 40 }]))
+                .unwrap();
+        assert_eq!(status[0].status, SearchJobState::Stopped);
+
+        let plugins: Vec<SearchPlugin> = serde_json::from_value(json!([{
+            "enabled": true,
+            "fullName": "Example",
+            "name": "example",
+            "supportedCategories": [{ "id": "all", "name": "All categories" }],
+            "url": "https://site.example",
+            "version": "1.2"
+        }]))
+        .unwrap();
+        assert_eq!(plugins[0].full_name, "Example");
+        assert_eq!(plugins[0].supported_categories[0].id, "all");
+    }
+}
@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::qbt::categories::Category;
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::{Error, Result};
+use crate::qbt::tags::Tags;
+use crate::qbt::torrents::TorrentInfo;
+
+/// Raw response of `sync/maindata`. Every field except `rid` is optional, as
+/// the server only sends what changed since the last response id.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(super) struct MainDataResponse {
+    /// Response ID
+    rid: i64,
+    /// Whether the response contains all the data or partial data
+    #[serde(default)]
+    full_update: bool,
+    /// Property: torrent hash, value: changed torrent fields
+    #[serde(default)]
+    torrents: HashMap<String, Map<String, Value>>,
+    /// List of hashes of torrents removed since last request
+    #[serde(default)]
+    torrents_removed: Vec<String>,
+    /// Info for categories added since last request
+    #[serde(default)]
+    categories: HashMap<String, Map<String, Value>>,
+    /// List of categories removed since last request
+    #[serde(default)]
+    categories_removed: Vec<String>,
+    /// List of tags added since last request
+    #[serde(default)]
+    tags: Vec<String>,
+    /// List of tags removed since last request
+    #[serde(default)]
+    tags_removed: Vec<String>,
+    /// Global transfer info
+    #[serde(default)]
+    server_state: Map<String, Value>,
+}
+
+/// Local mirror of the daemon state, kept up to date by
+/// `Client::sync_maindata`.
+#[derive(Debug, Default, Clone)]
+pub struct MainData {
+    /// Last response id seen, 0 before the first sync
+    pub rid: i64,
+    /// Torrent hash -> merged torrent fields
+    pub torrents: HashMap<String, Map<String, Value>>,
+    /// Category name -> merged category fields
+    pub categories: HashMap<String, Map<String, Value>>,
+    /// All known tags
+    pub tags: Tags,
+    /// Merged global transfer info
+    pub server_state: Map<String, Value>,
+}
+
+/// Changes applied to a `MainData` by a single sync.
+#[derive(Debug, Default, Clone)]
+pub struct MainDataDiff {
+    /// True if the server replaced the whole state
+    pub full_update: bool,
+    /// Hashes of torrents that were not known before
+    pub torrents_added: Vec<String>,
+    /// Hash -> names of the fields that changed, for already known torrents
+    pub torrents_changed: HashMap<String, Vec<String>>,
+    /// Hashes of torrents that are gone
+    pub torrents_removed: Vec<String>,
+    /// Names of categories added or edited
+    pub categories_changed: Vec<String>,
+    /// Names of categories that are gone
+    pub categories_removed: Vec<String>,
+    /// Tags that were not known before
+    pub tags_added: Vec<String>,
+    /// Tags that are gone
+    pub tags_removed: Vec<String>,
+    /// Names of the server state fields that changed
+    pub server_state_changed: Vec<String>,
+}
+
+impl MainDataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.torrents_added.is_empty()
+            && self.torrents_changed.is_empty()
+            && self.torrents_removed.is_empty()
+            && self.categories_changed.is_empty()
+            && self.categories_removed.is_empty()
+            && self.tags_added.is_empty()
+            && self.tags_removed.is_empty()
+            && self.server_state_changed.is_empty()
+    }
+}
+
+/// Merge `update` into `target`, returning the names of the fields whose value
+/// actually changed.
+pub(super) fn merge_fields(
+    target: &mut Map<String, Value>,
+    update: Map<String, Value>,
+) -> Vec<String> {
+    let mut changed = vec![];
+    for (key, value) in update {
+        if target.get(&key) != Some(&value) {
+            changed.push(key.clone());
+            target.insert(key, value);
+        }
+    }
+    changed
+}
+
+impl MainData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Typed view of a single torrent of the mirror.
+    pub fn torrent_info(&self, hash: &str) -> Result<TorrentInfo> {
+        let fields = self
+            .torrents
+            .get(hash)
+            .ok_or_else(|| Error::Unknown(format!("Unknown torrent: {}", hash)))?;
+        let mut fields = fields.clone();
+        fields.insert("hash".to_string(), Value::String(hash.to_string()));
+        let info = serde_json::from_value(Value::Object(fields)).map_err(|e| {
+            Error::InvalidResponse(format!("Incomplete torrent data: {}: {}", hash, e))
+        })?;
+        Ok(info)
+    }
+
+    /// Typed view of every torrent of the mirror.
+    pub fn torrent_infos(&self) -> Result<Vec<TorrentInfo>> {
+        self.torrents
+            .keys()
+            .map(|hash| self.torrent_info(hash))
+            .collect()
+    }
+
+    /// Typed view of a single category of the mirror.
+    pub fn category(&self, name: &str) -> Result<Category> {
+        let fields = self
+            .categories
+            .get(name)
+            .ok_or_else(|| Error::Unknown(format!("Unknown category: {}", name)))?;
+        let category = serde_json::from_value(Value::Object(fields.clone())).map_err(|e| {
+            Error::InvalidResponse(format!("Incomplete category data: {}: {}", name, e))
+        })?;
+        Ok(category)
+    }
+
+    pub(super) fn apply(&mut self, resp: MainDataResponse) -> MainDataDiff {
+        let mut diff = MainDataDiff {
+            full_update: resp.full_update,
+            ..Default::default()
+        };
+
+        if resp.full_update {
+            // Anything we know about which is absent from a full update is gone.
+            diff.torrents_removed = self
+                .torrents
+                .keys()
+                .filter(|x| !resp.torrents.contains_key(*x))
+                .cloned()
+                .collect();
+            diff.categories_removed = self
+                .categories
+                .keys()
+                .filter(|x| !resp.categories.contains_key(*x))
+                .cloned()
+                .collect();
+            diff.tags_removed = self
+                .tags
+                .iter()
+                .filter(|x| !resp.tags.contains(x))
+                .cloned()
+                .collect();
+
+            for hash in diff.torrents_removed.iter() {
+                self.torrents.remove(hash);
+            }
+            for name in diff.categories_removed.iter() {
+                self.categories.remove(name);
+            }
+            for tag in diff.tags_removed.iter() {
+                self.tags.remove(tag);
+            }
+            self.server_state.clear();
+        } else {
+            for hash in resp.torrents_removed {
+                if self.torrents.remove(&hash).is_some() {
+                    diff.torrents_removed.push(hash);
+                }
+            }
+            for name in resp.categories_removed {
+                if self.categories.remove(&name).is_some() {
+                    diff.categories_removed.push(name);
+                }
+            }
+            for tag in resp.tags_removed {
+                if self.tags.remove(&tag) {
+                    diff.tags_removed.push(tag);
+                }
+            }
+        }
+
+        for (hash, fields) in resp.torrents {
+            match self.torrents.get_mut(&hash) {
+                Some(torrent) => {
+                    let changed = merge_fields(torrent, fields);
+                    if !changed.is_empty() {
+                        diff.torrents_changed.insert(hash, changed);
+                    }
+                }
+                None => {
+                    self.torrents.insert(hash.clone(), fields);
+                    diff.torrents_added.push(hash);
+                }
+            }
+        }
+
+        for (name, fields) in resp.categories {
+            let category = self.categories.entry(name.clone()).or_default();
+            if !merge_fields(category, fields).is_empty() {
+                diff.categories_changed.push(name);
+            }
+        }
+
+        for tag in resp.tags {
+            if self.tags.insert(&tag) {
+                diff.tags_added.push(tag);
+            }
+        }
+
+        diff.server_state_changed = merge_fields(&mut self.server_state, resp.server_state);
+        self.rid = resp.rid;
+
+        diff
+    }
+}
+
+impl Client {
+    /// Fetch the changes since `state.rid` and merge them into `state`.
+    ///
+    /// Pass a fresh `MainData` to get a full snapshot, then keep passing the
+    /// same one to only download what changed.
+    pub fn sync_maindata(&self, state: &mut MainData) -> Result<MainDataDiff> {
+        let resp = self.send(&Endpoint::maindata(state.rid))?;
+        let result: MainDataResponse = parse_json(resp)?;
+        Ok(state.apply(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::qbt::torrents::tests::torrent_info;
+    use serde_json::json;
+
+    fn response(value: Value) -> MainDataResponse {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn full_update() -> MainDataResponse {
+        response(json!({
+            "rid": 1,
+            "full_update": true,
+            "torrents": {
+                "aaa": { "name": "a", "progress": 0.5, "state": "downloading" },
+                "bbb": { "name": "b", "progress": 1.0, "state": "uploading" }
+            },
+            "categories": { "linux": { "name": "linux", "savePath": "/linux" } },
+            "tags": ["iso"],
+            "server_state": { "dl_info_speed": 100, "connection_status": "connected" }
+        }))
+    }
+
+    #[test]
+    fn merge_fields_reports_changes() {
+        let mut target = json!({ "a": 1, "b": "x" }).as_object().unwrap().clone();
+        let update = json!({ "a": 1, "b": "y", "c": true })
+            .as_object()
+            .unwrap()
+            .clone();
+        let mut changed = merge_fields(&mut target, update);
+        changed.sort();
+        assert_eq!(changed, ["b", "c"]);
+        assert_eq!(
+            Value::Object(target),
+            json!({ "a": 1, "b": "y", "c": true })
+        );
+    }
+
+    #[test]
+    fn first_sync() {
+        let mut state = MainData::new();
+        let mut diff = state.apply(full_update());
+        diff.torrents_added.sort();
+
+        assert!(diff.full_update);
+        assert_eq!(state.rid, 1);
+        assert_eq!(diff.torrents_added, ["aaa", "bbb"]);
+        assert_eq!(diff.categories_changed, ["linux"]);
+        assert_eq!(diff.tags_added, ["iso"]);
+        assert_eq!(state.category("linux").unwrap().save_path, "/linux");
+        assert!(state.tags.contains("iso"));
+    }
+
+    #[test]
+    fn partial_sync() {
+        let mut state = MainData::new();
+        state.apply(full_update());
+        let diff = state.apply(response(json!({
+            "rid": 2,
+            "torrents": { "aaa": { "progress": 0.75 }, "bbb": { "progress": 1.0 } },
+            "torrents_removed": ["zzz"],
+            "categories_removed": ["linux"],
+            "tags": ["new"],
+            "tags_removed": ["iso"],
+            "server_state": { "dl_info_speed": 200 }
+        })));
+
+        assert!(!diff.full_update);
+        assert_eq!(state.rid, 2);
+        // Unchanged fields and unknown removals are not reported.
+        assert_eq!(diff.torrents_changed.len(), 1);
+        assert_eq!(diff.torrents_changed["aaa"], ["progress"]);
+        assert!(diff.torrents_removed.is_empty());
+        assert_eq!(diff.categories_removed, ["linux"]);
+        assert_eq!(diff.tags_added, ["new"]);
+        assert_eq!(diff.tags_removed, ["iso"]);
+        assert_eq!(diff.server_state_changed, ["dl_info_speed"]);
+        // Fields absent from the update are kept.
+        assert_eq!(state.torrents["aaa"]["name"], "a");
+        assert_eq!(state.server_state["connection_status"], "connected");
+
+        let diff = state.apply(response(json!({ "rid": 3 })));
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn full_update_drops_missing_entries() {
+        let mut state = MainData::new();
+        state.apply(full_update());
+        let diff = state.apply(response(json!({
+            "rid": 5,
+            "full_update": true,
+            "torrents": { "aaa": { "name": "a", "progress": 0.5, "state": "downloading" } }
+        })));
+
+        assert_eq!(diff.torrents_removed, ["bbb"]);
+        assert_eq!(diff.categories_removed, ["linux"]);
+        assert_eq!(diff.tags_removed, ["iso"]);
+        assert!(diff.torrents_changed.is_empty());
+        assert_eq!(state.torrents.len(), 1);
+        assert!(state.server_state.is_empty());
+    }
+
+    #[test]
+    fn typed_views() {
+        let mut state = MainData::new();
+        state.apply(full_update());
+        // The mirror only has a few fields of each torrent.
+        assert!(matches!(
+            state.torrent_info("aaa"),
+            Err(Error::InvalidResponse(_))
+        ));
+        assert!(matches!(state.torrent_info("zzz"), Err(Error::Unknown(_))));
+
+        // Torrents are keyed by hash, without a hash field.
+        let info = serde_json::to_value(torrent_info("ccc")).unwrap();
+        let mut fields = info.as_object().unwrap().clone();
+        fields.remove("hash");
+        state.torrents.insert("ccc".to_string(), fields);
+        assert_eq!(state.torrent_info("ccc").unwrap().hash, "ccc");
+        assert!(matches!(state.category("zzz"), Err(Error::Unknown(_))));
+    }
+}
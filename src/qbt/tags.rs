@@ -0,0 +1,191 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::btree_set::{self, BTreeSet};
+use std::fmt;
+
+use crate::qbt::bulk::TorrentSelector;
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::Result;
+use crate::qbt::torrents::Torrent;
+
+/// Set of tags, (de)serialized as the comma-concatenated list used by the
+/// Web API.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tags(BTreeSet<String>);
+
+impl Tags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a comma-concatenated tag list, e.g. "movies, 4k".
+    pub fn parse(tags: &str) -> Self {
+        Self(
+            tags.split(',')
+                .map(|x| x.trim())
+                .filter(|x| !x.is_empty())
+                .map(|x| x.to_string())
+                .collect(),
+        )
+    }
+
+    pub fn contains(&self, tag: &str) -> bool {
+        self.0.contains(tag)
+    }
+
+    pub fn insert(&mut self, tag: &str) -> bool {
+        self.0.insert(tag.to_string())
+    }
+
+    pub fn remove(&mut self, tag: &str) -> bool {
+        self.0.remove(tag)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> btree_set::Iter<'_, String> {
+        self.0.iter()
+    }
+}
+
+impl fmt::Display for Tags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tags: Vec<&str> = self.0.iter().map(|x| x.as_str()).collect();
+        write!(f, "{}", tags.join(","))
+    }
+}
+
+impl<'a> FromIterator<&'a str> for Tags {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self(iter.into_iter().map(|x| x.to_string()).collect())
+    }
+}
+
+impl FromIterator<String> for Tags {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<'a> IntoIterator for &'a Tags {
+    type Item = &'a String;
+    type IntoIter = btree_set::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl Serialize for Tags {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Tags {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tags = String::deserialize(deserializer)?;
+        Ok(Self::parse(&tags))
+    }
+}
+
+impl Client {
+    pub fn get_tags(&self) -> Result<Tags> {
+        let resp = self.send(&Endpoint::tags())?;
+        let result: Vec<String> = parse_json(resp)?;
+        Ok(result.into_iter().collect())
+    }
+
+    pub fn create_tags(&self, tags: &Tags) -> Result<()> {
+        self.send(&Endpoint::create_tags(tags))?;
+        Ok(())
+    }
+
+    /// Delete the tags, removing them from every torrent.
+    pub fn delete_tags(&self, tags: &Tags) -> Result<()> {
+        self.send(&Endpoint::delete_tags(tags))?;
+        Ok(())
+    }
+
+    pub fn remove_torrents_tags(&self, selector: &TorrentSelector, tags: &Tags) -> Result<()> {
+        let tags = tags.to_string();
+        self.torrents_action("torrents/removeTags", selector, &[("tags", &tags)])
+    }
+
+    /// Replace the tags of the torrents. Requires qBittorrent 5.1 (Web API
+    /// 2.11.4).
+    pub fn set_torrents_tags(&self, selector: &TorrentSelector, tags: &Tags) -> Result<()> {
+        let tags = tags.to_string();
+        self.torrents_action("torrents/setTags", selector, &[("tags", &tags)])
+    }
+}
+
+impl Torrent {
+    pub fn add_tags(&self, tags: &Tags) -> Result<()> {
+        self.client
+            .add_torrents_tags(&TorrentSelector::from(self), tags)
+    }
+
+    pub fn remove_tags(&self, tags: &Tags) -> Result<()> {
+        self.client
+            .remove_torrents_tags(&TorrentSelector::from(self), tags)
+    }
+
+    /// Replace the tags of this torrent. On servers without `torrents/setTags`
+    /// this falls back to removing and adding the difference with
+    /// `self.info.tags`.
+    pub fn set_tags(&self, tags: &Tags) -> Result<()> {
+        let selector = TorrentSelector::from(self);
+        if self.client.api_version_at_least((2, 11, 4))? {
+            return self.client.set_torrents_tags(&selector, tags);
+        }
+
+        let removed: Tags = self
+            .info
+            .tags
+            .iter()
+            .filter(|x| !tags.contains(x))
+            .cloned()
+            .collect();
+        let added: Tags = tags
+            .iter()
+            .filter(|x| !self.info.tags.contains(x))
+            .cloned()
+            .collect();
+
+        if !removed.is_empty() {
+            self.client.remove_torrents_tags(&selector, &removed)?;
+        }
+        if !added.is_empty() {
+            self.client.add_torrents_tags(&selector, &added)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comma_separated_tags() {
+        let tags = Tags::parse("movies, 4k,,movies ");
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains("movies") && tags.contains("4k"));
+        assert_eq!(tags.to_string(), "4k,movies");
+        assert!(Tags::parse("").is_empty());
+    }
+
+    #[test]
+    fn serializes_as_string() {
+        let tags: Tags = serde_json::from_str(r#""b,a""#).unwrap();
+        assert_eq!(tags, ["a", "b"].into_iter().collect());
+        assert_eq!(serde_json::to_string(&tags).unwrap(), r#""a,b""#);
+    }
+}
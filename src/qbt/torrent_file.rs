@@ -0,0 +1,419 @@
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+use crate::qbt::bencode::{Decoder, Value};
+use crate::qbt::creator::TorrentFormat;
+use crate::qbt::error::{Error, Result};
+use crate::qbt::torrents::TorrentInfo;
+
+/// File of a .torrent, as listed by `TorrentFile::files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    /// Path joined with '/', starting with the torrent name for torrents of
+    /// several files, like the file names of `torrents/files`
+    pub path: String,
+    /// File size (bytes)
+    pub length: u64,
+}
+
+/// A parsed .torrent file (BEP 3, BEP 52 for v2 and hybrid torrents).
+#[derive(Debug, Clone)]
+pub struct TorrentFile {
+    /// Top level dictionary, without "info"
+    root: Value,
+    /// The info dictionary
+    info: Value,
+    format: TorrentFormat,
+    info_hash_v1: Option<[u8; 20]>,
+    info_hash_v2: Option<[u8; 32]>,
+    /// See `hash`
+    hash: String,
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidTorrent(message.to_string())
+}
+
+/// Lowercase hex, as used for hashes by the Web API.
+pub(super) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+impl TorrentFile {
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        let mut decoder = Decoder::new(data);
+        let Value::Dict(mut root) = decoder.value(0)? else {
+            return Err(invalid("Not a dictionary"));
+        };
+        decoder.finish()?;
+
+        let info = root
+            .remove("info".as_bytes())
+            .filter(|x| x.as_dict().is_some())
+            .ok_or_else(|| invalid("Missing info dictionary"))?;
+        // Infohashes cover the info dictionary exactly as it was encoded.
+        let raw_info = decoder.raw("info").unwrap_or_default();
+
+        if info.get("name").and_then(Value::as_bytes).is_none() {
+            return Err(invalid("Missing name"));
+        }
+        if info
+            .get("piece length")
+            .and_then(Value::as_int)
+            .filter(|x| *x > 0)
+            .is_none()
+        {
+            return Err(invalid("Missing or invalid piece length"));
+        }
+
+        let v1 = match info.get("pieces") {
+            Some(x) => match x.as_bytes() {
+                Some(x) if x.len() % 20 == 0 => true,
+                _ => return Err(invalid("Invalid pieces")),
+            },
+            None => false,
+        };
+        let v2 = match info.get("meta version").and_then(Value::as_int) {
+            Some(2) if info.get("file tree").and_then(Value::as_dict).is_some() => true,
+            Some(2) => return Err(invalid("Missing file tree")),
+            Some(_) => return Err(invalid("Unsupported meta version")),
+            None => false,
+        };
+        let format = match (v1, v2) {
+            (true, true) => TorrentFormat::Hybrid,
+            (true, false) => TorrentFormat::V1,
+            (false, true) => TorrentFormat::V2,
+            (false, false) => return Err(invalid("Missing pieces")),
+        };
+        if v1 && info.get("length").is_none() && info.get("files").is_none() {
+            return Err(invalid("Missing length or files"));
+        }
+
+        let info_hash_v1: Option<[u8; 20]> = v1.then(|| Sha1::digest(raw_info).into());
+        let info_hash_v2: Option<[u8; 32]> = v2.then(|| Sha256::digest(raw_info).into());
+        let hash = match (&info_hash_v1, &info_hash_v2) {
+            (Some(x), _) => to_hex(x),
+            (None, Some(x)) => to_hex(&x[..20]),
+            (None, None) => return Err(invalid("Missing pieces")),
+        };
+
+        Ok(Self {
+            root: Value::Dict(root),
+            info,
+            format,
+            info_hash_v1,
+            info_hash_v2,
+            hash,
+        })
+    }
+
+    /// Encode the torrent back to bencode. Keys are written in canonical
+    /// order, which may differ from the original file but not from any
+    /// torrent produced by a conforming client.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut root = self.root.as_dict().cloned().unwrap_or_default();
+        root.insert(b"info".to_vec(), self.info.clone());
+        Value::Dict(root).encode()
+    }
+
+    /// Top level fields other than "info", for fields not exposed here.
+    pub fn root(&self) -> &Value {
+        &self.root
+    }
+
+    /// The info dictionary, for fields not exposed here.
+    pub fn info(&self) -> &Value {
+        &self.info
+    }
+
+    pub fn format(&self) -> TorrentFormat {
+        self.format
+    }
+
+    pub fn name(&self) -> String {
+        let name = self.info().get("name").and_then(Value::as_bytes);
+        String::from_utf8_lossy(name.unwrap_or_default()).into_owned()
+    }
+
+    /// Piece size (bytes)
+    pub fn piece_length(&self) -> u64 {
+        self.info()
+            .get("piece length")
+            .and_then(Value::as_int)
+            .unwrap_or_default() as u64
+    }
+
+    pub fn private(&self) -> bool {
+        self.info().get("private").and_then(Value::as_int) == Some(1)
+    }
+
+    /// SHA-1 infohash (hex) of v1 and hybrid torrents
+    pub fn info_hash_v1(&self) -> Option<String> {
+        self.info_hash_v1.map(|x| to_hex(&x))
+    }
+
+    /// SHA-256 infohash (hex) of v2 and hybrid torrents
+    pub fn info_hash_v2(&self) -> Option<String> {
+        self.info_hash_v2.map(|x| to_hex(&x))
+    }
+
+    /// Hash qBittorrent identifies the torrent by: the v1 infohash, or the v2
+    /// infohash truncated to 20 bytes for pure v2 torrents.
+    pub fn hash(&self) -> String {
+        self.hash.clone()
+    }
+
+    /// Whether `info` is this torrent, i.e. adding this file would be a
+    /// duplicate.
+    pub fn matches(&self, info: &TorrentInfo) -> bool {
+        info.hash.eq_ignore_ascii_case(&self.hash)
+    }
+
+    /// Tracker tiers, from "announce-list" or else "announce".
+    pub fn trackers(&self) -> Vec<Vec<String>> {
+        let to_strings = |x: &[Value]| -> Vec<String> {
+            x.iter()
+                .filter_map(|x| x.as_str().map(str::to_string))
+                .collect()
+        };
+
+        if let Some(tiers) = self.root.get("announce-list").and_then(Value::as_list) {
+            let tiers: Vec<Vec<String>> = tiers
+                .iter()
+                .filter_map(|x| x.as_list().map(to_strings))
+                .filter(|x| !x.is_empty())
+                .collect();
+            if !tiers.is_empty() {
+                return tiers;
+            }
+        }
+        match self.root.get("announce").and_then(Value::as_str) {
+            Some(x) => vec![vec![x.to_string()]],
+            None => vec![],
+        }
+    }
+
+    /// Files of the torrent, without the padding files of hybrid torrents.
+    pub fn files(&self) -> Vec<FileEntry> {
+        let info = self.info();
+        let name = self.name();
+        let mut files = vec![];
+
+        if let Some(tree) = info.get("file tree").and_then(Value::as_dict) {
+            // A single file torrent has the file itself at the top of its tree.
+            let single = tree.len() == 1 && tree.values().all(|x| x.get("").is_some());
+            let mut path = if single { vec![] } else { vec![name] };
+            walk_file_tree(tree, &mut path, &mut files);
+        } else if let Some(list) = info.get("files").and_then(Value::as_list) {
+            for file in list {
+                let padding = file
+                    .get("attr")
+                    .and_then(Value::as_bytes)
+                    .is_some_and(|x| x.contains(&b'p'));
+                if padding {
+                    continue;
+                }
+                let mut path = vec![name.clone()];
+                if let Some(x) = file.get("path").and_then(Value::as_list) {
+                    path.extend(
+                        x.iter()
+                            .filter_map(Value::as_bytes)
+                            .map(|x| String::from_utf8_lossy(x).into_owned()),
+                    );
+                }
+                files.push(FileEntry {
+                    path: path.join("/"),
+                    length: length_of(file),
+                });
+            }
+        } else {
+            files.push(FileEntry {
+                path: name,
+                length: length_of(info),
+            });
+        }
+
+        files
+    }
+
+    /// Total size (bytes) of the files
+    pub fn total_size(&self) -> u64 {
+        self.files().iter().map(|x| x.length).sum()
+    }
+}
+
+fn length_of(value: &Value) -> u64 {
+    value
+        .get("length")
+        .and_then(Value::as_int)
+        .unwrap_or_default()
+        .max(0) as u64
+}
+
+/// Collect the files of a v2 file tree, where a file is a node holding an
+/// empty key.
+fn walk_file_tree(
+    tree: &BTreeMap<Vec<u8>, Value>,
+    path: &mut Vec<String>,
+    files: &mut Vec<FileEntry>,
+) {
+    for (key, node) in tree {
+        path.push(String::from_utf8_lossy(key).into_owned());
+        match node.get("") {
+            Some(file) => files.push(FileEntry {
+                path: path.join("/"),
+                length: length_of(file),
+            }),
+            None => {
+                if let Some(x) = node.as_dict() {
+                    walk_file_tree(x, path, files);
+                }
+            }
+        }
+        path.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1: &str = concat!(
+        "d8:announce31:http://tracker.example/announce",
+        "4:infod6:lengthi1048576e4:name10:debian.iso12:piece lengthi262144e",
+        "6:pieces80:aaaaaaaaaaaaaaaaaaaabbbbbbbbbbbbbbbbbbbb",
+        "ccccccccccccccccccccddddddddddddddddddddee",
+    );
+
+    const FILE_TREE: &str = concat!(
+        "9:file treed",
+        "5:a.txtd0:d6:lengthi1000e11:pieces root32:rrrrrrrrrrrrrrrrrrrrrrrrrrrrrrrree",
+        "3:subd5:b.txtd0:d6:lengthi20000e11:pieces root32:sssssssssssssssssssssssssssssssseee",
+        "e",
+    );
+
+    fn v2() -> String {
+        [
+            "d8:announce31:http://tracker.example/announce4:infod",
+            FILE_TREE,
+            "12:meta versioni2e4:name4:test12:piece lengthi16384eee",
+        ]
+        .concat()
+    }
+
+    fn hybrid() -> String {
+        [
+            "d13:announce-listll25:http://a.example/announceel25:http://b.example/announce",
+            "25:http://c.example/announceee4:infod",
+            FILE_TREE,
+            "5:filesld6:lengthi1000e4:pathl5:a.txteed4:attr1:p6:lengthi15384e",
+            "4:pathl4:.pad5:15384eed6:lengthi20000e4:pathl3:sub5:b.txteee",
+            "12:meta versioni2e4:name4:test12:piece lengthi16384e",
+            "6:pieces60:eeeeeeeeeeeeeeeeeeeeffffffffffffffffffffggggggggggggggggggggee",
+        ]
+        .concat()
+    }
+
+    fn files(entries: &[(&str, u64)]) -> Vec<FileEntry> {
+        entries
+            .iter()
+            .map(|(path, length)| FileEntry {
+                path: path.to_string(),
+                length: *length,
+            })
+            .collect()
+    }
+
+    // Expected infohashes are SHA-1/SHA-256 of the info dictionaries,
+    // computed independently of this crate.
+
+    #[test]
+    fn v1_torrent() {
+        let torrent = TorrentFile::parse(V1.as_bytes()).unwrap();
+        assert_eq!(torrent.format(), TorrentFormat::V1);
+        assert_eq!(
+            torrent.info_hash_v1().unwrap(),
+            "45cc7084ea2069af7603c12707618082666aecfa"
+        );
+        assert_eq!(torrent.info_hash_v2(), None);
+        assert_eq!(torrent.hash(), "45cc7084ea2069af7603c12707618082666aecfa");
+        assert_eq!(torrent.name(), "debian.iso");
+        assert_eq!(torrent.piece_length(), 262144);
+        assert!(!torrent.private());
+        assert_eq!(
+            torrent.trackers(),
+            [["http://tracker.example/announce".to_string()]]
+        );
+        assert_eq!(torrent.files(), files(&[("debian.iso", 1048576)]));
+    }
+
+    #[test]
+    fn v2_torrent() {
+        let torrent = TorrentFile::parse(v2().as_bytes()).unwrap();
+        assert_eq!(torrent.format(), TorrentFormat::V2);
+        assert_eq!(torrent.info_hash_v1(), None);
+        assert_eq!(
+            torrent.info_hash_v2().unwrap(),
+            "d8b3e4eccd08c69000f839c626c034ef91d5ac0775fd24d265908972efc5a2da"
+        );
+        // Truncated v2 infohash
+        assert_eq!(torrent.hash(), "d8b3e4eccd08c69000f839c626c034ef91d5ac07");
+        assert_eq!(
+            torrent.files(),
+            files(&[("test/a.txt", 1000), ("test/sub/b.txt", 20000)])
+        );
+    }
+
+    #[test]
+    fn hybrid_torrent() {
+        let torrent = TorrentFile::parse(hybrid().as_bytes()).unwrap();
+        assert_eq!(torrent.format(), TorrentFormat::Hybrid);
+        assert_eq!(
+            torrent.info_hash_v1().unwrap(),
+            "f123bf946cbc68edfe00f62050faf4dd6056c8b3"
+        );
+        assert_eq!(
+            torrent.info_hash_v2().unwrap(),
+            "b0eed424c81987a16469cc334a0633b00043623ca3b3f509d5921e007bae916c"
+        );
+        assert_eq!(torrent.hash(), "f123bf946cbc68edfe00f62050faf4dd6056c8b3");
+        assert_eq!(torrent.trackers().len(), 2);
+        assert_eq!(torrent.trackers()[1].len(), 2);
+        // No padding file
+        assert_eq!(torrent.total_size(), 21000);
+    }
+
+    #[test]
+    fn hash_covers_original_bytes() {
+        // Keys out of order: re-encoding would change the infohash.
+        let data =
+            "d4:infod4:name1:x6:lengthi1e12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+        let torrent = TorrentFile::parse(data.as_bytes()).unwrap();
+        assert_eq!(torrent.hash(), "db9e470ad9a78e40a1fb29900d954332fb2d9932");
+        assert_ne!(torrent.encode(), data.as_bytes());
+    }
+
+    #[test]
+    fn round_trip() {
+        for data in [V1.to_string(), v2(), hybrid()] {
+            let torrent = TorrentFile::parse(data.as_bytes()).unwrap();
+            assert_eq!(torrent.encode(), data.as_bytes());
+        }
+    }
+
+    #[test]
+    fn invalid_torrents() {
+        for data in [
+            "le",
+            "d4:infod4:name1:xee",
+            "d4:infod4:name1:x12:piece lengthi0e6:pieces0:ee",
+            "d4:infod4:name1:x12:piece lengthi16384e6:pieces3:abcee",
+            "d4:infod4:name1:x12:piece lengthi16384e12:meta versioni2eee",
+            "d4:infod4:name1:x12:piece lengthi16384e6:pieces0:ee",
+        ] {
+            let e = TorrentFile::parse(data.as_bytes()).unwrap_err();
+            assert!(matches!(e, Error::InvalidTorrent(_)), "{}: {}", data, e);
+        }
+    }
+}
@@ -1,11 +1,16 @@
-use anyhow::Result;
 use chrono::DateTime;
 use chrono::{Local, TimeZone};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::{str::Split, time::SystemTime};
+use std::{str::Split, sync::Arc, time::SystemTime};
 
-use crate::qbt::core::Client;
+use crate::qbt::bulk::TorrentSelector;
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::{Error, Result};
+use crate::qbt::pieces::PieceMap;
+use crate::qbt::tags::Tags;
+use crate::qbt::transfer::ByteRate;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TorrentState {
@@ -86,8 +91,15 @@ pub struct TorrentInfo {
     pub f_l_piece_prio: bool,
     /// True if force start is enabled for this torrent
     pub force_start: bool,
-    /// Torrent hash
+    /// Torrent hash: the v1 infohash, or the truncated v2 infohash of pure
+    /// v2 torrents
     pub hash: String,
+    /// SHA-1 infohash, empty for pure v2 torrents and before qBittorrent 4.4
+    #[serde(default)]
+    pub infohash_v1: String,
+    /// SHA-256 infohash, empty for v1 torrents and before qBittorrent 4.4
+    #[serde(default)]
+    pub infohash_v2: String,
     /// Last time (Unix Epoch) when a chunk was downloaded/uploaded
     pub last_activity: i64,
     /// Magnet URI corresponding to this torrent
@@ -135,8 +147,8 @@ pub struct TorrentInfo {
     pub state: TorrentState,
     /// True if super seeding is enabled
     pub super_seeding: bool,
-    /// Comma-concatenated tag list of the torrent
-    pub tags: String,
+    /// Tags of the torrent, sent as a comma-concatenated list
+    pub tags: Tags,
     /// Total active time (seconds)
     pub time_active: i64,
     /// Total size (bytes) of all file in this torrent (including unselected
@@ -226,7 +238,52 @@ pub struct GenericInfo {
     pub up_speed: i64,
 }
 
-#[derive(Clone, Serialize_repr, Deserialize_repr, Debug)]
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum TrackerStatus {
+    /// Tracker is disabled (used for DHT, PeX, and LSD)
+    Disabled = 0,
+    /// Tracker has not been contacted yet
+    NotContacted = 1,
+    /// Tracker has been contacted and is working
+    Working = 2,
+    /// Tracker is updating
+    Updating = 3,
+    /// Tracker has been contacted, but it is not working (or doesn't send
+    /// proper replies)
+    NotWorking = 4,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Tracker {
+    /// Tracker url
+    pub url: String,
+    /// Tracker status
+    pub status: TrackerStatus,
+    /// Tracker priority tier. Lower tier trackers are tried before higher
+    /// tiers. -1 for DHT, PeX, and LSD.
+    pub tier: i64,
+    /// Number of peers for current torrent, as reported by the tracker
+    pub num_peers: i64,
+    /// Number of seeds for current torrent, as reported by the tracker
+    pub num_seeds: i64,
+    /// Number of leeches for current torrent, as reported by the tracker
+    pub num_leeches: i64,
+    /// Number of completed downloads for current torrent, as reported by the
+    /// tracker
+    pub num_downloaded: i64,
+    /// Tracker message (there is no way of knowing what this message is - it's
+    /// up to tracker admins)
+    pub msg: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WebSeed {
+    /// URL of the web seed
+    pub url: String,
+}
+
+#[derive(Clone, Copy, Serialize_repr, Deserialize_repr, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ItemPriority {
     DoNotDownload = 0,
@@ -236,7 +293,7 @@ pub enum ItemPriority {
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
-pub struct Item<'a> {
+pub struct Item {
     /// File index
     pub index: i64,
     /// File name (including relative path)
@@ -255,12 +312,49 @@ pub struct Item<'a> {
     pub availability: f32,
 
     #[serde(skip)]
-    pub torrent: Option<&'a Torrent<'a>>,
+    /// Torrent the file belongs to, shared by the files of a listing
+    pub torrent: Option<Arc<Torrent>>,
+}
+
+/// Share limits of a torrent. For every limit, -2 means the global limit is
+/// used and -1 means no limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShareLimits {
+    /// Maximum share ratio
+    pub ratio: f32,
+    /// Maximum seeding time (minutes)
+    pub seeding_time: i64,
+    /// Maximum inactive seeding time (minutes)
+    pub inactive_seeding_time: i64,
+}
+
+impl Default for ShareLimits {
+    fn default() -> Self {
+        Self {
+            ratio: -2.0,
+            seeding_time: -2,
+            inactive_seeding_time: -2,
+        }
+    }
+}
+
+impl ShareLimits {
+    /// Form fields of `torrents/setShareLimits`.
+    fn params(&self) -> [(&'static str, String); 3] {
+        [
+            ("ratioLimit", self.ratio.to_string()),
+            ("seedingTimeLimit", self.seeding_time.to_string()),
+            (
+                "inactiveSeedingTimeLimit",
+                self.inactive_seeding_time.to_string(),
+            ),
+        ]
+    }
 }
 
 #[derive(Clone, Debug)]
-pub struct Torrent<'a> {
-    pub client: &'a Client,
+pub struct Torrent {
+    pub client: Client,
     pub info: TorrentInfo,
     pub fetch_time: SystemTime,
 
@@ -268,25 +362,24 @@ pub struct Torrent<'a> {
     metadata_buffer: Option<Vec<u8>>,
 }
 
-impl<'a> Client {
-    pub fn get_torrent_list<C>(&'a self, container: &mut C) -> Result<()>
+impl Client {
+    pub fn get_torrent_list<C>(&self, container: &mut C) -> Result<()>
     where
-        C: Extend<Torrent<'a>>,
+        C: Extend<Torrent>,
     {
-        let endpoint = self.url("torrents/info");
-        let resp = self.session.get(endpoint).send()?;
-        let torrent_infos: Vec<TorrentInfo> = resp.json::<Vec<TorrentInfo>>()?;
+        let resp = self.send(&Endpoint::torrent_list())?;
+        let torrent_infos: Vec<TorrentInfo> = parse_json::<Vec<TorrentInfo>>(resp)?;
 
-        container.extend(torrent_infos.iter().map(|x| Torrent::new(&self, x.clone())));
+        container.extend(torrent_infos.iter().map(|x| Torrent::new(self, x.clone())));
 
         Ok(())
     }
 }
 
-impl<'a> Torrent<'a> {
-    pub fn new(client: &'a Client, info: TorrentInfo) -> Self {
+impl Torrent {
+    pub fn new(client: &Client, info: TorrentInfo) -> Self {
         Self {
-            client,
+            client: client.clone(),
             info,
             fetch_time: SystemTime::now(),
             metadata_buffer: None,
@@ -296,36 +389,72 @@ impl<'a> Torrent<'a> {
     /* Following are all part of the core QBT API */
 
     pub fn get_generic_properties(&self) -> Result<GenericInfo> {
-        let endpoint = self.client.url("torrents/properties");
-        let query = [("hash", &self.info.hash)];
-        let resp = self.client.session.get(endpoint).query(&query).send()?;
-        let result = resp.json()?;
+        let resp = self
+            .client
+            .send(&Endpoint::torrent_properties(&self.info.hash))?;
+        let result = parse_json(resp)?;
+        Ok(result)
+    }
+
+    pub fn get_trackers(&self) -> Result<Vec<Tracker>> {
+        let resp = self
+            .client
+            .send(&Endpoint::torrent_trackers(&self.info.hash))?;
+        let result = parse_json(resp)?;
+        Ok(result)
+    }
+
+    pub fn get_webseeds(&self) -> Result<Vec<WebSeed>> {
+        let resp = self
+            .client
+            .send(&Endpoint::torrent_webseeds(&self.info.hash))?;
+        let result = parse_json(resp)?;
         Ok(result)
     }
 
-    pub fn get_trackers(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/trackers");
-        todo!("Not implemented!");
+    pub fn add_trackers(&self, urls: &[&str]) -> Result<()> {
+        let urls = urls.join("\n");
+        self.post("torrents/addTrackers", &[("urls", &urls)])
+    }
+
+    pub fn edit_tracker(&self, orig_url: &str, new_url: &str) -> Result<()> {
+        let params = [("origUrl", orig_url), ("newUrl", new_url)];
+        self.post("torrents/editTracker", &params)
     }
 
-    pub fn get_webseeds(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/webseeds");
-        todo!("Not implemented!");
+    pub fn remove_trackers(&self, urls: &[&str]) -> Result<()> {
+        let urls = urls.join("|");
+        self.post("torrents/removeTrackers", &[("urls", &urls)])
     }
 
-    pub fn get_contents<C>(&'a self, container: &mut C) -> Result<()>
+    pub fn add_webseeds(&self, urls: &[&str]) -> Result<()> {
+        let urls = urls.join("|");
+        self.post("torrents/addWebSeeds", &[("urls", &urls)])
+    }
+
+    pub fn edit_webseed(&self, orig_url: &str, new_url: &str) -> Result<()> {
+        let params = [("origUrl", orig_url), ("newUrl", new_url)];
+        self.post("torrents/editWebSeed", &params)
+    }
+
+    pub fn remove_webseeds(&self, urls: &[&str]) -> Result<()> {
+        let urls = urls.join("|");
+        self.post("torrents/removeWebSeeds", &[("urls", &urls)])
+    }
+
+    /// Files of this torrent. They share `self` instead of each holding a
+    /// copy of the torrent.
+    pub fn get_contents<C>(self: &Arc<Self>, container: &mut C) -> Result<()>
     where
-        C: Extend<Item<'a>>,
+        C: Extend<Item>,
     {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/files");
-        let resp = self.client.session.get(endpoint).query(&query).send()?;
+        let resp = self
+            .client
+            .send(&Endpoint::torrent_files(&self.info.hash, None))?;
 
-        let mut my_items = resp.json::<Vec<Item>>()?;
+        let mut my_items = parse_json::<Vec<Item>>(resp)?;
         for i in my_items.iter_mut() {
-            i.torrent = Some(&self);
+            i.torrent = Some(Arc::clone(self));
         }
 
         container.extend(my_items);
@@ -333,54 +462,163 @@ impl<'a> Torrent<'a> {
         Ok(())
     }
 
-    pub fn get_single_item(&'a self, index: u64) -> Result<Item<'a>> {
-        let query = [("hash", &self.info.hash), ("indexes", &index.to_string())];
-        let endpoint = self.client.url("torrents/files");
-        let resp = self.client.session.get(endpoint).query(&query).send()?;
-        let item: [Item; 1] = resp.json()?;
-        Ok(item[0].clone())
+    pub fn get_single_item(self: &Arc<Self>, index: u64) -> Result<Item> {
+        let resp = self
+            .client
+            .send(&Endpoint::torrent_files(&self.info.hash, Some(index)))?;
+        let [mut item]: [Item; 1] = parse_json(resp)?;
+        item.torrent = Some(Arc::clone(self));
+        Ok(item)
+    }
+
+    /// Set the priority of the files with the given indexes.
+    pub fn set_file_priorities(&self, indexes: &[i64], priority: ItemPriority) -> Result<()> {
+        let ids: Vec<String> = indexes.iter().map(|x| x.to_string()).collect();
+        let ids = ids.join("|");
+        let priority = (priority as u8).to_string();
+        self.post(
+            "torrents/filePrio",
+            &[("id", &ids), ("priority", &priority)],
+        )
+    }
+
+    /// Rename a file, `old_path` and `new_path` being relative to the torrent
+    /// root (see `Item::name`).
+    pub fn rename_file(&self, old_path: &str, new_path: &str) -> Result<()> {
+        let params = [("oldPath", old_path), ("newPath", new_path)];
+        self.post("torrents/renameFile", &params)
+    }
+
+    /// Rename a folder, `old_path` and `new_path` being relative to the
+    /// torrent root.
+    pub fn rename_folder(&self, old_path: &str, new_path: &str) -> Result<()> {
+        let params = [("oldPath", old_path), ("newPath", new_path)];
+        self.post("torrents/renameFolder", &params)
+    }
+
+    pub fn get_piece_states(&self) -> Result<PieceMap> {
+        let resp = self.client.send(&Endpoint::piece_states(&self.info.hash))?;
+        let states: Vec<u8> = parse_json(resp)?;
+        PieceMap::from_raw(&states)
+    }
+
+    pub fn get_piece_hashes(&self) -> Result<Vec<String>> {
+        let resp = self.client.send(&Endpoint::piece_hashes(&self.info.hash))?;
+        let result = parse_json(resp)?;
+        Ok(result)
+    }
+
+    /// POST `endpoint` with this torrent as the `hash` field, for endpoints
+    /// which only accept a single torrent.
+    fn post(&self, endpoint: &'static str, params: &[(&'static str, &str)]) -> Result<()> {
+        let endpoint = Endpoint::torrent_action(endpoint, &self.info.hash).params(params);
+        self.client.send(&endpoint)?;
+        Ok(())
+    }
+
+    /// POST `endpoint` for this torrent, with `params` as extra form fields.
+    fn action(&self, endpoint: &'static str, params: &[(&'static str, &str)]) -> Result<()> {
+        self.client
+            .torrents_action(endpoint, &TorrentSelector::from(self), params)
     }
 
-    pub fn get_piece_states(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/pieceStates");
-        todo!("Not implemented!");
+    pub fn pause(&self) -> Result<()> {
+        self.client.pause_torrents(&TorrentSelector::from(self))
     }
 
-    pub fn get_piece_hashes(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/pieceHashes");
-        todo!("Not implemented!");
+    pub fn resume(&self) -> Result<()> {
+        self.client.resume_torrents(&TorrentSelector::from(self))
     }
 
-    pub fn pause(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/pause");
-        todo!("Not implemented!");
+    /// Remove the torrent, and its downloaded data if `delete_files` is set.
+    pub fn delete(&self, delete_files: bool) -> Result<()> {
+        self.client
+            .delete_torrents(&TorrentSelector::from(self), delete_files)
     }
 
-    pub fn resume(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/resume");
-        todo!("Not implemented!");
+    pub fn recheck(&self) -> Result<()> {
+        self.client.recheck_torrents(&TorrentSelector::from(self))
     }
 
-    pub fn delete(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/delete");
-        todo!("Not implemented!");
+    pub fn reannounce(&self) -> Result<()> {
+        self.client
+            .reannounce_torrents(&TorrentSelector::from(self))
     }
 
-    pub fn recheck(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/recheck");
-        todo!("Not implemented!");
+    pub fn set_download_limit(&self, limit: ByteRate) -> Result<()> {
+        self.client
+            .set_torrents_download_limit(&TorrentSelector::from(self), limit)
     }
 
-    pub fn reannounce(&self) {
-        let query = [("hash", &self.info.hash)];
-        let endpoint = self.client.url("torrents/reannounce");
-        todo!("Not implemented!");
+    pub fn set_upload_limit(&self, limit: ByteRate) -> Result<()> {
+        self.client
+            .set_torrents_upload_limit(&TorrentSelector::from(self), limit)
+    }
+
+    pub fn set_share_limits(&self, limits: &ShareLimits) -> Result<()> {
+        let params = limits.params();
+        let params: Vec<(&str, &str)> = params.iter().map(|(k, v)| (*k, v.as_str())).collect();
+        self.action("torrents/setShareLimits", &params)
+    }
+
+    /// Move the torrent data to `location`.
+    pub fn set_location(&self, location: &str) -> Result<()> {
+        self.action("torrents/setLocation", &[("location", location)])
+    }
+
+    /// Set the path where the torrent is kept while incomplete.
+    pub fn set_download_path(&self, path: &str) -> Result<()> {
+        let endpoint = Endpoint::set_download_path(&self.info.hash, path);
+        self.client.send(&endpoint)?;
+        Ok(())
+    }
+
+    pub fn rename(&self, name: &str) -> Result<()> {
+        self.post("torrents/rename", &[("name", name)])
+    }
+
+    pub fn set_auto_management(&self, enable: bool) -> Result<()> {
+        let enable = enable.to_string();
+        self.action("torrents/setAutoManagement", &[("enable", &enable)])
+    }
+
+    pub fn toggle_sequential_download(&self) -> Result<()> {
+        self.action("torrents/toggleSequentialDownload", &[])
+    }
+
+    pub fn toggle_first_last_piece_priority(&self) -> Result<()> {
+        self.action("torrents/toggleFirstLastPiecePrio", &[])
+    }
+
+    pub fn set_force_start(&self, value: bool) -> Result<()> {
+        let value = value.to_string();
+        self.action("torrents/setForceStart", &[("value", &value)])
+    }
+
+    pub fn set_super_seeding(&self, value: bool) -> Result<()> {
+        let value = value.to_string();
+        self.action("torrents/setSuperSeeding", &[("value", &value)])
+    }
+
+    /// Move the torrent one step up the queue. Requires queueing to be
+    /// enabled.
+    pub fn increase_prio(&self) -> Result<()> {
+        self.action("torrents/increasePrio", &[])
+    }
+
+    /// Move the torrent one step down the queue.
+    pub fn decrease_prio(&self) -> Result<()> {
+        self.action("torrents/decreasePrio", &[])
+    }
+
+    /// Move the torrent to the top of the queue.
+    pub fn top_prio(&self) -> Result<()> {
+        self.action("torrents/topPrio", &[])
+    }
+
+    /// Move the torrent to the bottom of the queue.
+    pub fn bottom_prio(&self) -> Result<()> {
+        self.action("torrents/bottomPrio", &[])
     }
 
     /* Following are additional nice to have features not part of the core API. */
@@ -468,7 +706,7 @@ impl<'a> Torrent<'a> {
     }
 }
 
-impl<'a> Item<'a> {
+impl Item {
     pub fn get_path_components(&self) -> Option<Split<'_, &str>> {
         if !self.name.contains("/") {
             return None;
@@ -476,4 +714,165 @@ impl<'a> Item<'a> {
 
         Some(self.name.split("/"))
     }
+
+    fn parent(&self) -> Result<&Torrent> {
+        self.torrent.as_deref().ok_or_else(|| {
+            Error::InvalidRequest(format!("Item is not attached to a torrent: {}", self.name))
+        })
+    }
+
+    pub fn set_priority(&mut self, priority: ItemPriority) -> Result<()> {
+        self.parent()?
+            .set_file_priorities(&[self.index], priority)?;
+        self.priority = priority;
+        Ok(())
+    }
+
+    /// Rename this file, `new_name` being relative to the torrent root.
+    pub fn rename(&mut self, new_name: &str) -> Result<()> {
+        self.parent()?.rename_file(&self.name, new_name)?;
+        self.name = new_name.to_string();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// A `torrents/info` entry, as sent by qBittorrent.
+    pub(in crate::qbt) fn torrent_info(hash: &str) -> TorrentInfo {
+        let info = r#"{
+            "added_on": 1704067200,
+            "amount_left": 0,
+            "auto_tmm": false,
+            "availability": -1,
+            "category": "linux",
+            "completed": 1048576,
+            "completion_on": 1704070800,
+            "content_path": "/downloads/debian.iso",
+            "dl_limit": -1,
+            "dlspeed": 0,
+            "downloaded": 1048576,
+            "downloaded_session": 0,
+            "eta": 8640000,
+            "f_l_piece_prio": false,
+            "force_start": false,
+            "hash": "HASH",
+            "last_activity": 1704070800,
+            "magnet_uri": "magnet:?xt=urn:btih:HASH&dn=debian.iso",
+            "max_ratio": -1,
+            "max_seeding_time": -1,
+            "name": "debian.iso",
+            "num_complete": 10,
+            "num_incomplete": 2,
+            "num_leechs": 0,
+            "num_seeds": 0,
+            "priority": 0,
+            "progress": 1,
+            "ratio": 0.5,
+            "ratio_limit": -2,
+            "save_path": "/downloads",
+            "seeding_time": 3600,
+            "seeding_time_limit": -2,
+            "seen_complete": 1704070800,
+            "seq_dl": false,
+            "size": 1048576,
+            "state": "uploading",
+            "super_seeding": false,
+            "tags": "iso, linux",
+            "time_active": 7200,
+            "total_size": 1048576,
+            "tracker": "",
+            "up_limit": -1,
+            "uploaded": 524288,
+            "uploaded_session": 0,
+            "upspeed": 0
+        }"#;
+        serde_json::from_str(&info.replace("HASH", hash)).unwrap()
+    }
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn handles_are_send_sync() {
+        // Torrents and files are handed to other threads, e.g. by the
+        // filesystem, along with their client.
+        assert_send_sync::<Client>();
+        assert_send_sync::<Torrent>();
+        assert_send_sync::<Item>();
+    }
+
+    #[test]
+    fn parse_torrent_info() {
+        let info = torrent_info("0123456789abcdef0123456789abcdef01234567");
+        assert_eq!(info.name, "debian.iso");
+        assert!(matches!(info.state, TorrentState::Uploading));
+        assert_eq!(info.tags.iter().count(), 2);
+    }
+
+    #[test]
+    fn parse_trackers() {
+        let trackers: Vec<Tracker> = serde_json::from_str(
+            r#"[
+                {"url": "** [DHT] **", "status": 0, "tier": -1, "num_peers": 12,
+                 "num_seeds": 0, "num_leeches": 0, "num_downloaded": 0, "msg": ""},
+                {"url": "udp://tracker.example:1337", "status": 4, "tier": 0,
+                 "num_peers": -1, "num_seeds": -1, "num_leeches": -1,
+                 "num_downloaded": -1, "msg": "timed out"}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(trackers[0].status, TrackerStatus::Disabled);
+        assert_eq!(trackers[1].status, TrackerStatus::NotWorking);
+        assert_eq!(trackers[1].msg, "timed out");
+
+        let webseeds: Vec<WebSeed> =
+            serde_json::from_str(r#"[{"url": "https://mirror.example/debian.iso"}]"#).unwrap();
+        assert_eq!(webseeds[0].url, "https://mirror.example/debian.iso");
+    }
+
+    #[test]
+    fn share_limits_params() {
+        let params = ShareLimits::default().params();
+        assert!(params.iter().all(|(_, value)| value == "-2"));
+
+        let limits = ShareLimits {
+            ratio: 1.5,
+            seeding_time: -1,
+            ..Default::default()
+        };
+        assert_eq!(
+            limits.params(),
+            [
+                ("ratioLimit", "1.5".to_string()),
+                ("seedingTimeLimit", "-1".to_string()),
+                ("inactiveSeedingTimeLimit", "-2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_items() {
+        let items: Vec<Item> = serde_json::from_str(
+            r#"[{"index": 1, "name": "debian/firmware/a.bin", "size": 1024,
+                 "progress": 0.5, "priority": 6, "piece_range": [3, 4],
+                 "availability": 1}]"#,
+        )
+        .unwrap();
+        let mut item = items[0].clone();
+        assert_eq!(item.priority, ItemPriority::High);
+        assert_eq!(item.is_seed, None);
+        let components: Vec<&str> = item.get_path_components().unwrap().collect();
+        assert_eq!(components, ["debian", "firmware", "a.bin"]);
+
+        // Only items listed through a torrent can be changed.
+        assert!(matches!(
+            item.set_priority(ItemPriority::DoNotDownload),
+            Err(Error::InvalidRequest(_))
+        ));
+        assert_eq!(item.priority, ItemPriority::High);
+        assert!(item.rename("b.bin").is_err());
+        assert_eq!(item.name, "debian/firmware/a.bin");
+    }
 }
@@ -1,7 +1,9 @@
-use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use crate::qbt::core::Client;
+use crate::qbt::core::{parse_json, Client};
+use crate::qbt::endpoint::Endpoint;
+use crate::qbt::error::{Error, Result};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ConnectionStatus {
@@ -13,6 +15,45 @@ pub enum ConnectionStatus {
     Disconnected,
 }
 
+/// Transfer rate limit (bytes/s). Zero means unlimited.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteRate(pub u64);
+
+impl ByteRate {
+    pub const UNLIMITED: ByteRate = ByteRate(0);
+
+    pub fn bytes(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub fn kib(kib: u64) -> Self {
+        Self(kib * 1024)
+    }
+
+    pub fn mib(mib: u64) -> Self {
+        Self(mib * 1024 * 1024)
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl fmt::Display for ByteRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Parse the plain text body of `transfer/downloadLimit` and
+/// `transfer/uploadLimit`.
+pub(super) fn parse_limit(text: &str) -> Result<ByteRate> {
+    text.trim()
+        .parse()
+        .map(ByteRate)
+        .map_err(|_| Error::InvalidResponse(format!("Invalid rate limit: {}", text)))
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct TransferInfo {
     /// Global download rate (bytes/s)
@@ -35,15 +76,13 @@ pub struct TransferInfo {
 
 impl Client {
     pub fn get_global_transfer_info(&self) -> Result<TransferInfo> {
-        let endpoint = self.url("transfer/info");
-        let resp = self.session.get(endpoint).send()?;
-        let result: TransferInfo = resp.json()?;
+        let resp = self.send(&Endpoint::transfer_info())?;
+        let result: TransferInfo = parse_json(resp)?;
         Ok(result)
     }
 
     pub fn alternative_speed_limits_enabled(&self) -> Result<bool> {
-        let endpoint = self.url("transfer/speedLimitsMode");
-        let resp = self.session.get(endpoint).send()?;
+        let resp = self.send(&Endpoint::speed_limits_mode())?;
         let result = match resp.text()?.as_str() {
             "1" => true,
             _ => false,
@@ -51,29 +90,60 @@ impl Client {
         Ok(result)
     }
 
-    pub fn toggle_alternative_speed_limits(&self) {
-        todo!("Not implemented!");
+    pub fn toggle_alternative_speed_limits(&self) -> Result<()> {
+        self.send(&Endpoint::toggle_speed_limits_mode())?;
+        Ok(())
+    }
+
+    /// Toggle the alternative speed limits only if they are not already in
+    /// the requested state.
+    pub fn set_alternative_speed_limits(&self, enabled: bool) -> Result<()> {
+        if self.alternative_speed_limits_enabled()? != enabled {
+            self.toggle_alternative_speed_limits()?;
+        }
+        Ok(())
     }
 
-    pub fn get_global_download_limit(&self) -> Result<u64> {
-        let endpoint = self.url("transfer/downloadLimit");
-        let resp = self.session.get(endpoint).send()?;
-        let result = resp.text()?.parse::<u64>()?;
+    pub fn get_global_download_limit(&self) -> Result<ByteRate> {
+        let resp = self.send(&Endpoint::download_limit())?;
+        let result = parse_limit(&resp.text()?)?;
         Ok(result)
     }
 
-    pub fn set_global_download_limit(&self) {
-        todo!("Not implemented!");
+    pub fn set_global_download_limit(&self, limit: ByteRate) -> Result<()> {
+        self.send(&Endpoint::set_download_limit(limit))?;
+        Ok(())
     }
 
-    pub fn get_global_upload_limit(&self) -> Result<u64> {
-        let endpoint = self.url("transfer/uploadLimit");
-        let resp = self.session.get(endpoint).send()?;
-        let result = resp.text()?.parse::<u64>()?;
+    pub fn get_global_upload_limit(&self) -> Result<ByteRate> {
+        let resp = self.send(&Endpoint::upload_limit())?;
+        let result = parse_limit(&resp.text()?)?;
         Ok(result)
     }
 
-    pub fn set_global_upload_limit(&self) {
-        todo!("Not implemented!");
+    pub fn set_global_upload_limit(&self, limit: ByteRate) -> Result<()> {
+        self.send(&Endpoint::set_upload_limit(limit))?;
+        Ok(())
+    }
+
+    /// Ban peers, given as "host:port".
+    pub fn ban_peers(&self, peers: &[&str]) -> Result<()> {
+        self.send(&Endpoint::ban_peers(peers))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_limits() {
+        assert_eq!(parse_limit("1024\n").unwrap(), ByteRate::kib(1));
+        assert!(parse_limit("0").unwrap().is_unlimited());
+        assert!(matches!(
+            parse_limit("fast"),
+            Err(Error::InvalidResponse(_))
+        ));
     }
 }